@@ -1,33 +1,79 @@
-use std::ops::Range;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    time::Duration,
+};
 
-use bevy::prelude::*;
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use thiserror::Error;
 
-use crate::screens::GameState;
+use crate::{aseprite, screens::GameState};
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(
-        Update,
-        (
-            execute_animations,
-            update_animation_graph::<PlayerAnimation>,
-            update_animation_graph::<EnemyAnimation>,
-        )
-            .run_if(in_state(GameState::Running)),
-    );
-}
-pub trait Action: Component {
-    fn as_animation(&self) -> AnimationConfig;
-}
-pub fn animation_bundle(action: impl Action) -> impl Bundle {
-    (action.as_animation(), action)
-}
-fn update_animation_graph<A>(mut actions: Query<(&mut AnimationConfig, &A), Changed<A>>)
-where
+    app.init_asset::<AnimationSet>()
+        .init_asset_loader::<AnimationSetAssetLoader>()
+        .init_asset_loader::<aseprite::AsepriteSheetAssetLoader>()
+        .add_event::<FrameReached<PlayerAnimation>>()
+        .add_event::<FrameReached<EnemyAnimation>>()
+        .add_event::<FrameReached<TowerAnimation>>()
+        .add_event::<ClipFinished<PlayerAnimation>>()
+        .add_event::<ClipFinished<EnemyAnimation>>()
+        .add_event::<ClipFinished<TowerAnimation>>()
+        .add_systems(
+            Update,
+            (
+                update_animation_graph::<PlayerAnimation>,
+                update_animation_graph::<EnemyAnimation>,
+                update_animation_graph::<TowerAnimation>,
+                execute_animations::<PlayerAnimation>,
+                execute_animations::<EnemyAnimation>,
+                execute_animations::<TowerAnimation>,
+            )
+                .chain()
+                .run_if(in_state(GameState::Running)),
+        );
+}
+pub trait Action: Component + Clone {
+    /// Every action, used to find the action of a clip
+    const ACTIONS: &'static [Self];
+    /// Name of the clip in the [`AnimationSet`] of the entity
+    fn clip(&self) -> &'static str;
+}
+/// The config is read from the [`AnimationSet`] by [`update_animation_graph`] once the action is added
+pub fn animation_bundle(action: impl Action, animations: Handle<AnimationSet>) -> impl Bundle {
+    (AnimationConfig::default(), Animations(animations), action)
+}
+/// Picks the clip of the changed action. Changing the animation set file also updates every entity that uses it
+fn update_animation_graph<A>(
+    mut events: EventReader<AssetEvent<AnimationSet>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    mut actions: Query<(&mut AnimationConfig, Ref<A>, &Animations)>,
+) where
     A: Action,
 {
-    for (mut config, action) in &mut actions {
+    let modified: HashSet<_> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    for (mut config, action, animations) in &mut actions {
+        if !action.is_changed() && !modified.contains(&animations.0.id()) {
+            continue;
+        }
+        let Some(animation_set) = animation_sets.get(animations.0.id()) else {
+            continue;
+        };
+        let Some(clip) = animation_set.clips.get(action.clip()) else {
+            warn!("animation set has no clip '{}'", action.clip());
+            continue;
+        };
         let flip = config.flip_sprites;
-        *config = action.as_animation();
+        *config = AnimationConfig::new(clip);
         config.flip_sprites = flip;
     }
 }
@@ -40,12 +86,18 @@ pub enum PlayerAnimation {
     DashSprint,
 }
 impl Action for PlayerAnimation {
-    fn as_animation(&self) -> AnimationConfig {
+    const ACTIONS: &'static [Self] = &[
+        PlayerAnimation::Idle,
+        PlayerAnimation::Running,
+        PlayerAnimation::Dash,
+        PlayerAnimation::DashSprint,
+    ];
+    fn clip(&self) -> &'static str {
         match self {
-            PlayerAnimation::Idle => AnimationConfig::new(0..4, 2),
-            PlayerAnimation::Running => AnimationConfig::new(6..17, 8),
-            PlayerAnimation::Dash => AnimationConfig::new(5..17, 16),
-            PlayerAnimation::DashSprint => AnimationConfig::new(18..24, 16),
+            PlayerAnimation::Idle => "idle",
+            PlayerAnimation::Running => "running",
+            PlayerAnimation::Dash => "dash",
+            PlayerAnimation::DashSprint => "dash_sprint",
         }
     }
 }
@@ -58,30 +110,145 @@ pub enum EnemyAnimation {
     DashTargeted,
 }
 impl Action for EnemyAnimation {
-    fn as_animation(&self) -> AnimationConfig {
+    const ACTIONS: &'static [Self] = &[
+        EnemyAnimation::Spawn,
+        EnemyAnimation::Idle,
+        EnemyAnimation::Running,
+        EnemyAnimation::Explode,
+        EnemyAnimation::DashTargeted,
+    ];
+    fn clip(&self) -> &'static str {
+        match self {
+            EnemyAnimation::Spawn => "spawn",
+            EnemyAnimation::Idle => "idle",
+            EnemyAnimation::Running => "running",
+            EnemyAnimation::Explode => "explode",
+            EnemyAnimation::DashTargeted => "dash_targeted",
+        }
+    }
+}
+/// The fire on top of a lit tower
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub enum TowerAnimation {
+    Burning,
+}
+impl Action for TowerAnimation {
+    const ACTIONS: &'static [Self] = &[TowerAnimation::Burning];
+    fn clip(&self) -> &'static str {
         match self {
-            EnemyAnimation::Spawn => AnimationConfig::new(13..16, 5),
-            EnemyAnimation::Idle => AnimationConfig::new(0..2, 2),
-            EnemyAnimation::Running => AnimationConfig::new(2..6, 8),
-            EnemyAnimation::Explode => AnimationConfig::new(6..13, 7),
-            EnemyAnimation::DashTargeted => AnimationConfig::new(0..2, 5),
+            TowerAnimation::Burning => "burning",
         }
     }
 }
+/// Sent when an entity shows the next frame of the clip of `action`, including the first one
+#[derive(Event, Clone, Debug)]
+pub struct FrameReached<A: Action> {
+    pub entity: Entity,
+    pub action: A,
+    /// Frame of the clip, starting at 0
+    pub frame: usize,
+}
+/// Sent when the last frame of the clip of `action` was shown. Looping clips send it on every loop,
+/// ping-pong clips once they are back at the first frame
+#[derive(Event, Clone, Debug)]
+pub struct ClipFinished<A: Action> {
+    pub entity: Entity,
+    pub action: A,
+}
+/// The animation set the clips of an entity are taken from
+#[derive(Component)]
+pub struct Animations(pub Handle<AnimationSet>);
+/// Named clips of a sprite sheet. Loaded from `*.anim.ron` files or Aseprite sheets, see [`aseprite`]
+#[derive(Asset, TypePath)]
+pub struct AnimationSet {
+    /// Also available as the `layout` label of the file, like `animations/player.anim.ron#layout`
+    #[dependency]
+    pub layout: Handle<TextureAtlasLayout>,
+    pub clips: HashMap<String, Clip>,
+}
+/// Format of `*.anim.ron` files
+#[derive(serde::Deserialize)]
+struct AnimationSetDef {
+    /// Width and height of a single frame in pixel
+    frame_size: UVec2,
+    columns: u32,
+    rows: u32,
+    clips: HashMap<String, Clip>,
+}
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct Clip {
+    /// Atlas index of the first frame
+    pub start: usize,
+    /// Atlas index after the last frame
+    pub end: usize,
+    pub fps: f32,
+    /// Seconds each frame is shown. Frames without a duration are shown for `1 / fps` seconds
+    #[serde(default)]
+    pub durations: Vec<f32>,
+    #[serde(default)]
+    pub mode: PlaybackMode,
+}
+#[derive(serde::Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub enum PlaybackMode {
+    #[default]
+    Loop,
+    /// Plays the clip once and keeps showing the last frame
+    Once,
+    /// Plays the clip once and continues with the named clip, like `Then("idle")`.
+    /// The action of the entity is switched as well if there is one for the clip
+    Then(String),
+    /// Plays the clip forwards and backwards again
+    PingPong,
+}
 #[derive(Component, Clone, Debug)]
 pub struct AnimationConfig {
     index: Range<usize>,
+    /// How long each frame of the clip is shown
+    durations: Vec<Duration>,
     frame_timer: Timer,
+    mode: PlaybackMode,
     pub flip_sprites: bool,
     new: bool,
+    /// Set once a clip that is not repeated reached the end
+    finished: bool,
+    /// A ping-pong clip on the way back
+    reverse: bool,
+}
+/// Shows the current frame until the clip is known
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        AnimationConfig {
+            index: 0..1,
+            durations: Vec::new(),
+            frame_timer: Timer::default(),
+            mode: PlaybackMode::Once,
+            flip_sprites: false,
+            new: false,
+            finished: true,
+            reverse: false,
+        }
+    }
 }
 impl AnimationConfig {
-    pub fn new(index: Range<usize>, fps: u8) -> Self {
+    pub fn new(clip: &Clip) -> Self {
+        let index = clip.start..clip.end.max(clip.start + 1);
+        let frame_time = Duration::from_secs_f32(1. / clip.fps.max(0.1));
+        let durations: Vec<_> = (0..index.len())
+            .map(|frame| {
+                clip.durations
+                    .get(frame)
+                    .map_or(frame_time, |secs| Duration::from_secs_f32(secs.max(0.)))
+            })
+            .collect();
         AnimationConfig {
+            frame_timer: Timer::new(durations[0], TimerMode::Once),
             index,
-            frame_timer: Timer::from_seconds(1. / fps as f32, TimerMode::Once),
+            durations,
+            mode: clip.mode.clone(),
             flip_sprites: false,
             new: true,
+            finished: false,
+            reverse: false,
         }
     }
     pub fn is_new(&mut self) -> bool {
@@ -92,34 +259,204 @@ impl AnimationConfig {
             false
         }
     }
-    pub fn last_sprite_index(&self) -> usize {
-        self.index.end - 1
-    }
-}
-fn execute_animations(time: Res<Time>, mut query: Query<(&mut AnimationConfig, &mut Sprite)>) {
-    for (mut config, mut sprite) in &mut query {
-        if let Some(atlas) = &mut sprite.texture_atlas {
-            if config.is_new() {
-                atlas.index = config.index.start;
+    /// The atlas index that follows `index`, if there is one, and whether the clip finished
+    fn next_index(&mut self, index: usize) -> (Option<usize>, bool) {
+        let Range { start, end } = self.index;
+        if self.reverse {
+            let next = index.saturating_sub(1).max(start);
+            if next == start {
+                self.reverse = false;
+            }
+            return (Some(next), next == start);
+        }
+        if index + 1 < end {
+            return (Some(index + 1), false);
+        }
+        match self.mode {
+            PlaybackMode::Loop => (Some(start), true),
+            PlaybackMode::PingPong if end - start > 2 => {
+                self.reverse = true;
+                (Some(end - 2), false)
             }
+            PlaybackMode::PingPong => (Some(start), true),
+            PlaybackMode::Once | PlaybackMode::Then(_) => (None, true),
         }
+    }
+    fn frame_duration(&self, index: usize) -> Duration {
+        self.durations
+            .get(index.saturating_sub(self.index.start))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+fn execute_animations<A>(
+    time: Res<Time>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    mut query: Query<(
+        Entity,
+        &mut AnimationConfig,
+        &mut Sprite,
+        &mut A,
+        &Animations,
+    )>,
+    mut frame_events: EventWriter<FrameReached<A>>,
+    mut finished_events: EventWriter<ClipFinished<A>>,
+) where
+    A: Action,
+{
+    for (entity, mut config, mut sprite, mut action, animations) in &mut query {
         if sprite.flip_x != config.flip_sprites {
             sprite.flip_x = config.flip_sprites;
         }
+        let Some(atlas) = &mut sprite.texture_atlas else {
+            continue;
+        };
+        if config.is_new() {
+            atlas.index = config.index.start;
+            frame_events.write(FrameReached {
+                entity,
+                action: action.clone(),
+                frame: 0,
+            });
+        }
+        if config.finished {
+            continue;
+        }
         // We track how long the current sprite has been displayed for
         config.frame_timer.tick(time.delta());
 
-        // If it has been displayed for the user-defined amount of time (fps)...
-        if config.frame_timer.just_finished() {
-            let Some(atlas) = &mut sprite.texture_atlas else {
+        // If it has been displayed for the duration of the frame...
+        if !config.frame_timer.just_finished() {
+            continue;
+        }
+        let (next, finished) = config.next_index(atlas.index);
+        if finished {
+            finished_events.write(ClipFinished {
+                entity,
+                action: action.clone(),
+            });
+        }
+        let Some(next) = next else {
+            config.finished = true;
+            let PlaybackMode::Then(clip_name) = config.mode.clone() else {
                 continue;
             };
-            if atlas.index >= config.index.end - 1 {
-                atlas.index = config.index.start;
-            } else {
-                atlas.index += 1;
+            // the graph picks the clip of the new action
+            if let Some(next_action) = A::ACTIONS.iter().find(|a| a.clip() == clip_name) {
+                *action = next_action.clone();
+                continue;
             }
-            config.frame_timer.reset();
-        }
+            let Some(clip) = animation_sets
+                .get(animations.0.id())
+                .and_then(|animation_set| animation_set.clips.get(&clip_name))
+            else {
+                warn!("animation set has no clip '{clip_name}'");
+                continue;
+            };
+            let flip = config.flip_sprites;
+            *config = AnimationConfig::new(clip);
+            config.flip_sprites = flip;
+            continue;
+        };
+        atlas.index = next;
+        config.frame_timer = Timer::new(config.frame_duration(next), TimerMode::Once);
+        frame_events.write(FrameReached {
+            entity,
+            action: action.clone(),
+            frame: next.saturating_sub(config.index.start),
+        });
+    }
+}
+#[derive(Default)]
+pub struct AnimationSetAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum AnimationSetAssetLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+}
+impl AssetLoader for AnimationSetAssetLoader {
+    type Asset = AnimationSet;
+    type Settings = ();
+    type Error = AnimationSetAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let def = ron::de::from_bytes::<AnimationSetDef>(&bytes)?;
+        let layout =
+            TextureAtlasLayout::from_grid(def.frame_size, def.columns, def.rows, None, None);
+        Ok(AnimationSet {
+            layout: load_context.add_labeled_asset("layout".into(), layout),
+            clips: def.clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation(clip: &str) -> AnimationConfig {
+        AnimationConfig::new(&ron::from_str::<Clip>(clip).unwrap())
+    }
+    /// The indices shown after `start` together with whether the clip finished
+    fn play(
+        config: &mut AnimationConfig,
+        start: usize,
+        frames: usize,
+    ) -> Vec<(Option<usize>, bool)> {
+        let mut index = start;
+        (0..frames)
+            .map(|_| {
+                let next = config.next_index(index);
+                index = next.0.unwrap_or(index);
+                next
+            })
+            .collect()
+    }
+    #[test]
+    fn loops_back_to_the_first_frame() {
+        let mut config = animation("(start: 2, end: 5, fps: 10.)");
+        assert_eq!(
+            play(&mut config, 2, 3),
+            [(Some(3), false), (Some(4), false), (Some(2), true)]
+        );
+    }
+    #[test]
+    fn plays_ping_pong_forwards_and_backwards() {
+        let mut config = animation("(start: 0, end: 4, fps: 10., mode: PingPong)");
+        let indices: Vec<_> = play(&mut config, 0, 8)
+            .into_iter()
+            .map(|(index, _)| index.unwrap())
+            .collect();
+        assert_eq!(indices, [1, 2, 3, 2, 1, 0, 1, 2]);
+        let mut config = animation("(start: 0, end: 4, fps: 10., mode: PingPong)");
+        assert_eq!(play(&mut config, 0, 6)[5], (Some(0), true));
+    }
+    #[test]
+    fn stops_at_the_last_frame() {
+        let mut config = animation("(start: 0, end: 2, fps: 10., mode: Once)");
+        assert_eq!(play(&mut config, 0, 2), [(Some(1), false), (None, true)]);
+        let mut config = animation("(start: 0, end: 2, fps: 10., mode: Then(\"idle\"))");
+        assert_eq!(play(&mut config, 1, 1), [(None, true)]);
+    }
+    #[test]
+    fn falls_back_to_fps_for_missing_durations() {
+        let config = animation("(start: 0, end: 3, fps: 10., durations: [0.5])");
+        assert_eq!(
+            config.durations,
+            [0.5, 0.1, 0.1].map(Duration::from_secs_f32)
+        );
     }
 }
@@ -0,0 +1,238 @@
+//! Import of sprite sheets exported by [Aseprite](https://www.aseprite.org/) as `.aseprite.json` files.
+//!
+//! The sheet has to be exported with the frames as an array and with the tags in the meta data.
+//! Each frame becomes a rect of the [`TextureAtlasLayout`] and each tag a [`Clip`] of the [`AnimationSet`],
+//! so tags have to be named like the clips of the actions, like `idle` or `running`.
+//! Frame durations are taken from the file. Tags repeated once are played once and
+//! `pingpong` tags are played as [`PlaybackMode::PingPong`].
+//! Reversed tags and other repeat counts can not be played and are rejected.
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use thiserror::Error;
+
+use crate::animation::{AnimationSet, Clip, PlaybackMode};
+
+#[derive(Default)]
+pub struct AsepriteSheetAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum AsepriteSheetAssetLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported Aseprite sheet: {0}")]
+    Unsupported(String),
+}
+impl AssetLoader for AsepriteSheetAssetLoader {
+    type Asset = AnimationSet;
+    type Settings = ();
+    type Error = AsepriteSheetAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let sheet = serde_json::from_slice::<AsepriteSheet>(&bytes)?;
+        let (layout, clips) = sheet.into_animation_set()?;
+        Ok(AnimationSet {
+            layout: load_context.add_labeled_asset("layout".into(), layout),
+            clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite.json"]
+    }
+}
+#[derive(serde::Deserialize)]
+struct AsepriteSheet {
+    /// An array of [`AsepriteFrame`]. Frames exported as a hash are keyed by file name and lose their order
+    frames: serde_json::Value,
+    meta: AsepriteMeta,
+}
+#[derive(serde::Deserialize)]
+struct AsepriteFrame {
+    frame: AsepriteRect,
+    #[serde(default)]
+    rotated: bool,
+    /// In milliseconds
+    duration: u32,
+}
+#[derive(serde::Deserialize)]
+struct AsepriteRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+#[derive(serde::Deserialize)]
+struct AsepriteMeta {
+    size: AsepriteSize,
+    #[serde(default, rename = "frameTags")]
+    frame_tags: Vec<AsepriteTag>,
+}
+#[derive(serde::Deserialize)]
+struct AsepriteSize {
+    w: u32,
+    h: u32,
+}
+#[derive(serde::Deserialize)]
+struct AsepriteTag {
+    name: String,
+    /// First and last frame of the tag, both included
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+    /// How often the tag is played, missing for endless repetition
+    #[serde(default)]
+    repeat: Option<String>,
+}
+impl AsepriteSheet {
+    fn into_animation_set(
+        self,
+    ) -> Result<(TextureAtlasLayout, HashMap<String, Clip>), AsepriteSheetAssetLoaderError> {
+        if !self.frames.is_array() {
+            return Err(AsepriteSheetAssetLoaderError::Unsupported(
+                "frames have to be exported as an array".into(),
+            ));
+        }
+        let frames = serde_json::from_value::<Vec<AsepriteFrame>>(self.frames)?;
+        let mut layout =
+            TextureAtlasLayout::new_empty(UVec2::new(self.meta.size.w, self.meta.size.h));
+        for AsepriteFrame { frame, rotated, .. } in &frames {
+            if *rotated {
+                return Err(AsepriteSheetAssetLoaderError::Unsupported(
+                    "rotated frames".into(),
+                ));
+            }
+            layout.add_texture(URect::new(
+                frame.x,
+                frame.y,
+                frame.x + frame.w,
+                frame.y + frame.h,
+            ));
+        }
+        let mut clips = HashMap::new();
+        for tag in self.meta.frame_tags {
+            if tag.from > tag.to || tag.to >= frames.len() {
+                return Err(AsepriteSheetAssetLoaderError::Unsupported(format!(
+                    "tag '{}' is outside of the frames",
+                    tag.name
+                )));
+            }
+            let durations: Vec<_> = frames[tag.from..=tag.to]
+                .iter()
+                .map(|frame| frame.duration as f32 / 1000.)
+                .collect();
+            let pingpong = match tag.direction.as_str() {
+                "" | "forward" => false,
+                "pingpong" => true,
+                direction => {
+                    return Err(AsepriteSheetAssetLoaderError::Unsupported(format!(
+                        "direction '{direction}' of tag '{}'",
+                        tag.name
+                    )));
+                }
+            };
+            let mode = match tag.repeat.as_deref() {
+                Some("1") => PlaybackMode::Once,
+                None if pingpong => PlaybackMode::PingPong,
+                None => PlaybackMode::Loop,
+                Some(repeat) => {
+                    return Err(AsepriteSheetAssetLoaderError::Unsupported(format!(
+                        "repeat count {repeat} of tag '{}'",
+                        tag.name
+                    )));
+                }
+            };
+            let clip = Clip {
+                start: tag.from,
+                end: tag.to + 1,
+                fps: 1. / durations[0].max(0.001),
+                durations,
+                mode,
+            };
+            clips.insert(tag.name, clip);
+        }
+        Ok((layout, clips))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three 16x16 frames in a row
+    fn sheet(frames: &str, tags: &str) -> AsepriteSheet {
+        serde_json::from_str(&format!(
+            r#"{{"frames": {frames}, "meta": {{"size": {{"w": 48, "h": 16}}, "frameTags": [{tags}]}}}}"#
+        ))
+        .unwrap()
+    }
+    const FRAMES: &str = r#"[
+        {"frame": {"x": 0, "y": 0, "w": 16, "h": 16}, "duration": 100},
+        {"frame": {"x": 16, "y": 0, "w": 16, "h": 16}, "duration": 100},
+        {"frame": {"x": 32, "y": 0, "w": 16, "h": 16}, "duration": 250}
+    ]"#;
+    #[test]
+    fn maps_tags_to_clips() {
+        let sheet = sheet(
+            FRAMES,
+            r#"{"name": "idle", "from": 0, "to": 1, "direction": "forward"},
+               {"name": "attack", "from": 2, "to": 2, "direction": "forward", "repeat": "1"},
+               {"name": "bounce", "from": 0, "to": 2, "direction": "pingpong"}"#,
+        );
+        let (layout, clips) = sheet.into_animation_set().unwrap();
+        assert_eq!(layout.size, UVec2::new(48, 16));
+        assert_eq!(layout.textures.len(), 3);
+        assert_eq!(layout.textures[2], URect::new(32, 0, 48, 16));
+        let idle = &clips["idle"];
+        assert_eq!((idle.start, idle.end), (0, 2));
+        assert_eq!(idle.durations, [0.1, 0.1]);
+        assert_eq!(idle.mode, PlaybackMode::Loop);
+        let attack = &clips["attack"];
+        assert_eq!((attack.start, attack.end), (2, 3));
+        assert_eq!(attack.durations, [0.25]);
+        assert_eq!(attack.mode, PlaybackMode::Once);
+        assert_eq!(clips["bounce"].mode, PlaybackMode::PingPong);
+    }
+    #[test]
+    fn rejects_frames_exported_as_hash() {
+        let frames =
+            r#"{"idle 0.png": {"frame": {"x": 0, "y": 0, "w": 16, "h": 16}, "duration": 100}}"#;
+        assert!(matches!(
+            sheet(frames, "").into_animation_set(),
+            Err(AsepriteSheetAssetLoaderError::Unsupported(_))
+        ));
+    }
+    #[test]
+    fn rejects_tags_outside_of_the_frames() {
+        let sheet = sheet(FRAMES, r#"{"name": "idle", "from": 1, "to": 3}"#);
+        assert!(matches!(
+            sheet.into_animation_set(),
+            Err(AsepriteSheetAssetLoaderError::Unsupported(_))
+        ));
+    }
+    #[test]
+    fn rejects_reversed_and_repeated_tags() {
+        for tag in [
+            r#"{"name": "idle", "from": 0, "to": 1, "direction": "reverse"}"#,
+            r#"{"name": "idle", "from": 0, "to": 1, "direction": "pingpong_reverse"}"#,
+            r#"{"name": "idle", "from": 0, "to": 1, "direction": "forward", "repeat": "3"}"#,
+        ] {
+            assert!(matches!(
+                sheet(FRAMES, tag).into_animation_set(),
+                Err(AsepriteSheetAssetLoaderError::Unsupported(_))
+            ));
+        }
+    }
+}
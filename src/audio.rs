@@ -4,19 +4,19 @@ use bevy::{
 };
 
 use crate::{
-    animation::PlayerAnimation, asset_loading::LoadResource, entity::Portal, screens::GameState,
+    animation::PlayerAnimation, asset_loading::LoadResource, editor::EditorMeta, entity::Portal,
+    io::SaveFile, screens::GameState,
 };
 const MAIN_TRACK_PATH: &str = "audio/hnoss_main.ogg";
 const DASH_TRACK: &str = "audio/dash.ogg";
 const SUCCESS_TRACK: &str = "audio/success.ogg";
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::Running), setup)
-        .init_resource::<SoundStore>()
+    app.init_resource::<SoundStore>()
         .load_resource::<SoundStore>()
         .add_systems(
             Update,
-            (check_events, fade_in).run_if(in_state(GameState::Running)),
+            (check_events, level_music, fade_in).run_if(in_state(GameState::Running)),
         );
 }
 fn check_events(
@@ -48,18 +48,45 @@ fn check_events(
         ));
     }
 }
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
-        AudioPlayer::new(asset_server.load(MAIN_TRACK_PATH)),
-        PlaybackSettings {
-            volume: Volume::Linear(0.0),
-            speed: 1.2,
-            mode: PlaybackMode::Loop,
-            ..default()
-        },
-        MainMusic,
-        AudioFadeIn,
-    ));
+/// Switches the background track whenever a level with different music is loaded
+fn level_music(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<SaveFile>>,
+    save_files: Res<Assets<SaveFile>>,
+    editor_meta: Res<EditorMeta>,
+    asset_server: Res<AssetServer>,
+    music: Query<(Entity, &MainMusic)>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if *id != editor_meta.current_level.id() {
+            continue;
+        }
+        let Some(level) = save_files.get(*id) else {
+            continue;
+        };
+        let track = level.meta.music.as_deref().unwrap_or(MAIN_TRACK_PATH);
+        if music.iter().any(|(_, current)| current.0 == track) {
+            continue;
+        }
+        for (e, _) in &music {
+            commands.entity(e).despawn();
+        }
+        commands.spawn((
+            AudioPlayer::new(asset_server.load(track.to_string())),
+            PlaybackSettings {
+                volume: Volume::Linear(0.0),
+                speed: 1.2,
+                mode: PlaybackMode::Loop,
+                ..default()
+            },
+            MainMusic(track.into()),
+            AudioFadeIn,
+        ));
+    }
 }
 
 #[derive(Component)]
@@ -88,8 +115,9 @@ fn fade_in(
     }
 }
 
+/// Holds the asset path of the track that is playing
 #[derive(Component)]
-struct MainMusic;
+struct MainMusic(String);
 #[derive(Component)]
 struct EventMusic;
 #[derive(Resource, Asset, TypePath)]
@@ -0,0 +1,127 @@
+//! Tile groups that pick their atlas index from their neighbours, like the edges of a pit.
+//!
+//! Groups are defined per tileset, see [`crate::map::TilesetDef::autotile`].
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use bevy::prelude::*;
+
+pub const NORTH: u8 = 0b0001;
+pub const EAST: u8 = 0b0010;
+pub const SOUTH: u8 = 0b0100;
+pub const WEST: u8 = 0b1000;
+
+#[derive(Reflect, Debug, Clone, serde::Deserialize)]
+pub struct AutotileGroup {
+    /// Shown on the brush in the editor
+    pub name: String,
+    /// Atlas index by the neighbours that belong to the same group.
+    /// The mask is a combination of [`NORTH`], [`EAST`], [`SOUTH`] and [`WEST`], so `0b1010` means east and west
+    pub variants: BTreeMap<u8, usize>,
+    /// Neighbours that never change the variant
+    #[serde(default)]
+    pub ignore: u8,
+    /// Used for masks without a variant. This is also the tile the brush places before the variant is picked
+    pub default: usize,
+}
+impl AutotileGroup {
+    pub fn contains(&self, index: usize) -> bool {
+        index == self.default || self.variants.values().any(|variant| *variant == index)
+    }
+    pub fn variant(&self, mask: u8) -> usize {
+        self.variants
+            .get(&(mask & !self.ignore))
+            .copied()
+            .unwrap_or(self.default)
+    }
+    /// Picks the variant of every tile of the group at `changed` or next to it.
+    /// Returns the tiles whose index has to change, including all tiles of the group at `changed`
+    pub fn resolve(
+        &self,
+        tiles: &HashMap<UVec2, usize>,
+        changed: &HashSet<UVec2>,
+        map_size: UVec2,
+    ) -> Vec<(UVec2, usize)> {
+        let is_member = |pos: UVec2| tiles.get(&pos).is_some_and(|index| self.contains(*index));
+        let neighbours = |pos: UVec2| {
+            [
+                (Some(pos + UVec2::Y), NORTH),
+                (Some(pos + UVec2::X), EAST),
+                (pos.y.checked_sub(1).map(|y| UVec2::new(pos.x, y)), SOUTH),
+                (pos.x.checked_sub(1).map(|x| UVec2::new(x, pos.y)), WEST),
+            ]
+            .into_iter()
+            .filter_map(move |(pos, direction)| {
+                pos.filter(|pos| pos.cmplt(map_size).all())
+                    .map(|pos| (pos, direction))
+            })
+        };
+        let mut affected: Vec<UVec2> = changed
+            .iter()
+            .flat_map(|pos| std::iter::once(*pos).chain(neighbours(*pos).map(|(pos, _)| pos)))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter(|pos| is_member(*pos))
+            .collect();
+        affected.sort_by_key(|pos| (pos.y, pos.x));
+        affected
+            .into_iter()
+            .filter_map(|pos| {
+                let mask = neighbours(pos)
+                    .filter(|(neighbour, _)| is_member(*neighbour))
+                    .fold(0, |mask, (_, direction)| mask | direction);
+                let index = self.variant(mask);
+                (changed.contains(&pos) || tiles[&pos] != index).then_some((pos, index))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Horizontal platform with separate left and right ends
+    fn group() -> AutotileGroup {
+        ron::from_str(
+            "(name: \"Platform\", variants: {0b0010: 11, 0b1000: 12, 0b1010: 13}, ignore: 0b0001, default: 10)",
+        )
+        .unwrap()
+    }
+    #[test]
+    fn picks_variant_by_neighbours() {
+        let group = group();
+        assert_eq!(group.variant(EAST | WEST), 13);
+        assert_eq!(group.variant(NORTH | EAST), 11);
+        assert_eq!(group.variant(SOUTH), 10);
+        assert!(group.contains(12));
+        assert!(!group.contains(14));
+    }
+    #[test]
+    fn updates_painted_tile_and_neighbours() {
+        let tiles = HashMap::from([
+            (UVec2::new(0, 0), 11),
+            (UVec2::new(1, 0), 12),
+            (UVec2::new(2, 0), 10),
+            (UVec2::new(2, 1), 7),
+        ]);
+        let changed = HashSet::from([UVec2::new(2, 0)]);
+        // the left end stays as it is, only the old right end becomes a middle piece
+        assert_eq!(
+            group().resolve(&tiles, &changed, UVec2::new(3, 2)),
+            [(UVec2::new(1, 0), 13), (UVec2::new(2, 0), 12)]
+        );
+    }
+    #[test]
+    fn keeps_neighbours_that_fit() {
+        let tiles = HashMap::from([
+            (UVec2::new(0, 0), 11),
+            (UVec2::new(1, 0), 13),
+            (UVec2::new(2, 0), 12),
+            (UVec2::new(2, 1), 7),
+        ]);
+        // tiles of other groups are not changed either
+        let changed = HashSet::from([UVec2::new(2, 1)]);
+        let resolved = group().resolve(&tiles, &changed, UVec2::new(3, 2));
+        assert!(resolved.is_empty(), "{resolved:?}");
+    }
+}
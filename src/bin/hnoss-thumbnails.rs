@@ -0,0 +1,59 @@
+//! Renders every level in `assets/level/` to a PNG with the same name.
+//!
+//! Usage: `hnoss-thumbnails [assets directory] [output directory]`.
+//! The output directory defaults to `thumbnails` and is created if it does not exist.
+use std::{fs, path::Path, process::ExitCode};
+
+use hnoss::{io, thumbnail};
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let assets = args.next().unwrap_or_else(|| "assets".into());
+    let output = args.next().unwrap_or_else(|| "thumbnails".into());
+    let (assets, output) = (Path::new(&assets), Path::new(&output));
+    let atlases = match thumbnail::TileAtlas::read_all(assets) {
+        Ok(atlases) => atlases,
+        Err(err) => {
+            eprintln!("could not read the tilesets: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let paths = match io::level_files(&assets.join("level")) {
+        Ok(paths) => paths,
+        Err(err) => {
+            eprintln!("could not read the level directory: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(err) = fs::create_dir_all(output) {
+        eprintln!("could not create {}: {err}", output.display());
+        return ExitCode::FAILURE;
+    }
+    let mut failed = false;
+    for path in paths {
+        let level = fs::read(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| io::parse_save_file(&bytes).map_err(|err| err.to_string()));
+        let level = match level {
+            Ok(level) => level,
+            Err(err) => {
+                eprintln!("skipping {}: {err}", path.display());
+                failed = true;
+                continue;
+            }
+        };
+        let target = output.join(path.with_extension("png").file_name().unwrap());
+        match thumbnail::render_level(&level, &atlases).save(&target) {
+            Ok(()) => println!("{}", target.display()),
+            Err(err) => {
+                eprintln!("could not write {}: {err}", target.display());
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,93 @@
+//! Checks every level in `assets/level/` and prints the problems as JSON.
+//!
+//! Usage: `hnoss-validate [assets directory]`.
+//! Exits with a non zero code if a level has errors or could not be read, warnings are only reported.
+//! Templates like the empty level are skipped.
+use std::{collections::HashMap, fs, path::Path, process::ExitCode};
+
+use hnoss::{
+    io,
+    map::{LayerType, TilesetDef},
+    validate::{self, Problem, Severity, TilesetInfo},
+};
+
+/// Levels that are meant to be copied instead of played
+const TEMPLATES: &[&str] = &["empty"];
+
+#[derive(serde::Serialize)]
+struct Report {
+    levels: Vec<LevelReport>,
+}
+#[derive(serde::Serialize)]
+struct LevelReport {
+    path: String,
+    /// Set if the level could not be read at all
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    problems: Vec<Problem>,
+}
+
+fn main() -> ExitCode {
+    let assets = std::env::args().nth(1).unwrap_or_else(|| "assets".into());
+    let assets = Path::new(&assets);
+    let mut tilesets = HashMap::new();
+    for layer_type in (0..3).map(LayerType::from_u8) {
+        let path = assets.join(layer_type.tileset_path());
+        let tileset = fs::read(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| {
+                ron::de::from_bytes::<TilesetDef>(&bytes).map_err(|err| err.to_string())
+            });
+        match tileset {
+            Ok(tileset) => {
+                tilesets.insert(layer_type, TilesetInfo::from(tileset));
+            }
+            Err(err) => {
+                eprintln!("could not read tileset {}: {err}", path.display());
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let paths = match io::level_files(&assets.join("level")) {
+        Ok(paths) => paths,
+        Err(err) => {
+            eprintln!("could not read the level directory: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut report = Report { levels: Vec::new() };
+    for path in paths {
+        let is_template = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| TEMPLATES.contains(&name));
+        if is_template {
+            continue;
+        }
+        let level = fs::read(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| io::parse_save_file(&bytes).map_err(|err| err.to_string()));
+        let (error, problems) = match level {
+            Ok(level) => (None, validate::validate_level(&level, &tilesets)),
+            Err(err) => (Some(err), Vec::new()),
+        };
+        report.levels.push(LevelReport {
+            path: path.display().to_string(),
+            error,
+            problems,
+        });
+    }
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    let failed = report.levels.iter().any(|level| {
+        level.error.is_some()
+            || level
+                .problems
+                .iter()
+                .any(|problem| problem.severity == Severity::Error)
+    });
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
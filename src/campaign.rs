@@ -0,0 +1,128 @@
+//! A campaign lists the levels of a level pack in the order they are played.
+//!
+//! Levels are referenced by the same name as in [`EditorEvents::LoadLevel`],
+//! so levels can be reordered or shared between packs without renaming files.
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{asset_loading::LoadResource, editor::EditorEvents};
+
+pub const MAIN_CAMPAIGN_PATH: &str = "campaigns/main.campaign.ron";
+/// Used when no campaign could be loaded
+pub const FALLBACK_LEVEL: &str = "level0";
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<CampaignProgress>()
+        .load_resource::<CampaignProgress>()
+        .add_systems(Update, start_campaign);
+}
+#[derive(Asset, TypePath, serde::Deserialize, Debug)]
+pub struct Campaign {
+    pub name: String,
+    pub chapters: Vec<Chapter>,
+}
+#[derive(serde::Deserialize, Debug)]
+pub struct Chapter {
+    pub name: String,
+    pub levels: Vec<String>,
+}
+impl Campaign {
+    /// All levels of all chapters in playing order
+    pub fn levels(&self) -> impl Iterator<Item = &str> {
+        self.chapters
+            .iter()
+            .flat_map(|chapter| chapter.levels.iter().map(String::as_str))
+    }
+    pub fn first_level(&self) -> Option<&str> {
+        self.levels().next()
+    }
+    pub fn contains(&self, level: &str) -> bool {
+        self.levels().any(|l| l == level)
+    }
+    /// The level that follows `level`, also across chapters.
+    /// [`None`] if `level` is the last level or not part of this campaign
+    pub fn level_after(&self, level: &str) -> Option<&str> {
+        self.levels().skip_while(|l| *l != level).nth(1)
+    }
+    pub fn chapter_of(&self, level: &str) -> Option<&Chapter> {
+        self.chapters
+            .iter()
+            .find(|chapter| chapter.levels.iter().any(|l| l == level))
+    }
+}
+/// The campaign that is currently played
+#[derive(Resource, Asset, TypePath)]
+pub struct CampaignProgress {
+    #[dependency]
+    pub campaign: Handle<Campaign>,
+    /// The first level of the campaign gets loaded as soon as the campaign is available
+    pub start_pending: bool,
+}
+impl FromWorld for CampaignProgress {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        CampaignProgress {
+            campaign: asset_server.load(MAIN_CAMPAIGN_PATH),
+            start_pending: false,
+        }
+    }
+}
+impl CampaignProgress {
+    pub fn first_level(&self, campaigns: &Assets<Campaign>) -> String {
+        campaigns
+            .get(self.campaign.id())
+            .and_then(Campaign::first_level)
+            .unwrap_or(FALLBACK_LEVEL)
+            .into()
+    }
+}
+fn start_campaign(
+    mut progress: ResMut<CampaignProgress>,
+    campaigns: Res<Assets<Campaign>>,
+    asset_server: Res<AssetServer>,
+    mut event_writer: EventWriter<EditorEvents>,
+) {
+    if !progress.start_pending {
+        return;
+    }
+    if let Some(LoadState::Failed(err)) = asset_server.get_load_state(progress.campaign.id()) {
+        warn!("failed loading campaign {err:?}");
+        progress.start_pending = false;
+        return;
+    }
+    let Some(campaign) = campaigns.get(progress.campaign.id()) else {
+        return;
+    };
+    info!("starting campaign '{}'", campaign.name);
+    progress.start_pending = false;
+    event_writer.write(EditorEvents::LoadLevel {
+        name: Some(progress.first_level(&campaigns)),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn campaign() -> Campaign {
+        ron::from_str(
+            r#"(name: "Main", chapters: [
+                (name: "Cave", levels: ["level1", "level2"]),
+                (name: "Empty", levels: []),
+                (name: "Tower", levels: ["level3"]),
+            ])"#,
+        )
+        .unwrap()
+    }
+    #[test]
+    fn finds_the_next_level_across_chapters() {
+        let campaign = campaign();
+        assert_eq!(campaign.level_after("level1"), Some("level2"));
+        assert_eq!(campaign.level_after("level2"), Some("level3"));
+    }
+    #[test]
+    fn has_no_level_after_the_last_or_an_unknown_level() {
+        let campaign = campaign();
+        assert_eq!(campaign.level_after("level3"), None);
+        assert_eq!(campaign.level_after("level9"), None);
+    }
+}
@@ -1,10 +1,10 @@
 use crate::{
     MainCamera,
-    animation::EnemyAnimation,
+    animation::{ClipFinished, EnemyAnimation},
     editor::RemoveOnLevelSwap,
     entity::{Enemy, Player, PlayerMode, Portal, Tower, TowerCountdown},
     map::Textures,
-    movement::{ACTIVE_TIME, TIRED_TIME},
+    movement::TIRED_TIME,
     screens::GameState,
 };
 use bevy::{color::palettes::tailwind::PURPLE_50, prelude::*};
@@ -56,37 +56,43 @@ fn update_player_mode(
 fn despawn_enemies(
     mut commands: Commands,
     mut tower_countdown: ResMut<TowerCountdown>,
-    mut enemies: Query<(&mut Visibility, &mut EnemyAnimation, &Transform, &Sprite), With<Enemy>>,
+    mut finished_clips: EventReader<ClipFinished<EnemyAnimation>>,
+    mut enemies: Query<(&mut Visibility, &mut EnemyAnimation, &Transform), With<Enemy>>,
     mut towers: Query<(&mut Tower, &mut Visibility, &Transform), Without<Enemy>>,
 ) {
-    for (mut visibility, mut enemy_animation, enemy_transform, sprite) in &mut enemies {
-        // 12 is the last sprite of the explode animation of slimes
-        // TODO better way
-        if sprite.texture_atlas.as_ref().unwrap().index == 12 {
-            commands.spawn((
-                ExplosionIndicator {
-                    timer: Timer::from_seconds(1., TimerMode::Once),
-                    position: enemy_transform.translation.xy(),
-                },
-                RemoveOnLevelSwap,
-            ));
-            for (mut tower, mut tower_visibility, tower_transform) in &mut towers {
-                if *tower_visibility == Visibility::Hidden
-                    && tower.activatable
-                    && enemy_transform
-                        .translation
-                        .distance(tower_transform.translation)
-                        < ENEMY_EXPLOSION_RADIUS
-                {
-                    *tower_visibility = Visibility::Inherited;
-                    tower.active = true;
-                    tower_countdown.timer = Some(Timer::new(ACTIVE_TIME, TimerMode::Once));
-                }
+    for event in finished_clips.read() {
+        if event.action != EnemyAnimation::Explode {
+            continue;
+        }
+        let Ok((mut visibility, mut enemy_animation, enemy_transform)) =
+            enemies.get_mut(event.entity)
+        else {
+            continue;
+        };
+        commands.spawn((
+            ExplosionIndicator {
+                timer: Timer::from_seconds(1., TimerMode::Once),
+                position: enemy_transform.translation.xy(),
+            },
+            RemoveOnLevelSwap,
+        ));
+        for (mut tower, mut tower_visibility, tower_transform) in &mut towers {
+            if *tower_visibility == Visibility::Hidden
+                && tower.activatable
+                && enemy_transform
+                    .translation
+                    .distance(tower_transform.translation)
+                    < ENEMY_EXPLOSION_RADIUS
+            {
+                *tower_visibility = Visibility::Inherited;
+                tower.active = true;
+                let burn_time = tower.burn_time.unwrap_or(tower_countdown.duration);
+                tower_countdown.timer = Some(Timer::new(burn_time, TimerMode::Once));
             }
-            *enemy_animation = EnemyAnimation::Spawn;
-
-            *visibility = Visibility::Hidden;
         }
+        *enemy_animation = EnemyAnimation::Spawn;
+
+        *visibility = Visibility::Hidden;
     }
 }
 
@@ -0,0 +1,751 @@
+//! Browser for the levels listed in [`io::LEVEL_INDEX_PATH`], opened with the Load button.
+//!
+//! Every level is shown with a thumbnail and can be loaded, duplicated, renamed, deleted or exported as file.
+//! Type the new name and confirm with `Enter` or discard with `Escape`.
+//! Imported files are added to the index like new levels.
+//! Deleting a level needs a second click. `Shift` + Load still starts the campaign from its first level.
+//! On the web, where there is no save dialog, new levels are saved by typing their name in the same way.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    color::palettes::css::CRIMSON,
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+    tasks::{IoTaskPool, Task, futures::check_ready},
+};
+use image::DynamicImage;
+
+use super::{
+    EditorEvents, EditorMeta, HOVERED_BUTTON, LevelTiles, NORMAL_BUTTON, PRESSED_BUTTON,
+    UiRespawnTrigger, notification::Notification,
+};
+use crate::{
+    io::{self, LevelIndex, SaveError, SaveFile},
+    map::{self, BACKGROUND_COLOR, LayerType, TILESIZE},
+    screens::GameState,
+    thumbnail::{self, TileAtlas},
+    widget::DEAD_BACKGROUND,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LevelLibrary>()
+        .init_resource::<BrowserState>()
+        .init_resource::<PendingImport>()
+        .init_resource::<SavePrompt>()
+        .add_observer(toggle_level_browser)
+        .add_observer(open_save_prompt)
+        .add_systems(
+            Update,
+            (
+                browser_button_system,
+                type_level_name,
+                type_save_name,
+                update_name_texts,
+                update_thumbnails,
+                finish_import,
+                refresh_level_browser,
+            )
+                .chain()
+                .run_if(in_state(GameState::Running)),
+        );
+}
+#[derive(Resource)]
+struct LevelLibrary {
+    index: Handle<LevelIndex>,
+    /// Levels listed in the browser, kept loaded for their thumbnails
+    levels: HashMap<String, Handle<SaveFile>>,
+    thumbnails: HashMap<String, Handle<Image>>,
+}
+impl FromWorld for LevelLibrary {
+    fn from_world(world: &mut World) -> Self {
+        LevelLibrary {
+            index: world.resource::<AssetServer>().load(io::LEVEL_INDEX_PATH),
+            levels: HashMap::new(),
+            thumbnails: HashMap::new(),
+        }
+    }
+}
+#[derive(Resource, Default)]
+pub(super) struct BrowserState {
+    /// The level that is renamed together with the typed name
+    renaming: Option<(String, String)>,
+    /// The level that is deleted on the next click on its delete button
+    confirm_delete: Option<String>,
+}
+/// Name the level is saved under once it is confirmed, see [`UiRespawnTrigger::SavePrompt`]
+#[derive(Resource, Default)]
+pub(super) struct SavePrompt(Option<String>);
+/// Run condition for keyboard shortcuts, so typing a name does not trigger them
+pub(super) fn not_typing(state: Res<BrowserState>, prompt: Res<SavePrompt>) -> bool {
+    state.renaming.is_none() && prompt.0.is_none()
+}
+/// File name and content of the file picked for import. [`None`] if the dialog was cancelled
+#[derive(Resource, Default)]
+struct PendingImport(Option<Task<Option<(String, Vec<u8>)>>>);
+#[derive(Component)]
+struct LevelBrowserUiRoot;
+/// Shows the typed name of the [`SavePrompt`]
+#[derive(Component)]
+struct SavePromptUiRoot;
+/// Shows the thumbnail of the level once it is rendered
+#[derive(Component)]
+struct LevelThumbnail(String);
+/// Shows the name of the level or the typed name while it is renamed
+#[derive(Component)]
+struct LevelName(String);
+#[derive(Component)]
+enum BrowserButton {
+    Load(String),
+    Duplicate(String),
+    Rename(String),
+    Delete(String),
+    Export(String),
+    New,
+    Import,
+    /// Picks a level outside of the index, like a Tiled map, with the file dialog of the system
+    OpenFile,
+}
+fn toggle_level_browser(
+    trigger: Trigger<UiRespawnTrigger>,
+    mut commands: Commands,
+    mut library: ResMut<LevelLibrary>,
+    mut state: ResMut<BrowserState>,
+    indices: Res<Assets<LevelIndex>>,
+    asset_server: Res<AssetServer>,
+    panels: Query<Entity, With<LevelBrowserUiRoot>>,
+) {
+    let close = match trigger.event() {
+        UiRespawnTrigger::LevelBrowserToggle => !panels.is_empty(),
+        UiRespawnTrigger::LevelBrowserRespawn => false,
+        _ => return,
+    };
+    for e in &panels {
+        commands.entity(e).despawn();
+    }
+    if close {
+        *state = BrowserState::default();
+        return;
+    }
+    let names = indices
+        .get(library.index.id())
+        .map(|index| index.levels.clone())
+        .unwrap_or_default();
+    library.levels.retain(|name, _| names.contains(name));
+    library.thumbnails.retain(|name, _| names.contains(name));
+    for name in &names {
+        library
+            .levels
+            .entry(name.clone())
+            .or_insert_with(|| asset_server.load(io::level_path(name)));
+    }
+    commands
+        .spawn((
+            Node {
+                left: Val::Percent(25.),
+                top: Val::Percent(8.),
+                width: Val::Percent(50.),
+                max_height: Val::Percent(80.),
+                padding: UiRect::all(Val::Px(8.)),
+                row_gap: Val::Px(6.),
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::scroll_y(),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            BackgroundColor(DEAD_BACKGROUND.with_alpha(0.9)),
+            LevelBrowserUiRoot,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(6.),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(browser_button(BrowserButton::New, "New level"));
+                    parent.spawn(browser_button(BrowserButton::Import, "Import"));
+                    if !cfg!(target_arch = "wasm32") {
+                        parent.spawn(browser_button(BrowserButton::OpenFile, "Open file"));
+                    }
+                });
+            for name in names {
+                let thumbnail = match library.thumbnails.get(&name) {
+                    Some(image) => ImageNode::new(image.clone()),
+                    None => ImageNode::solid_color(BACKGROUND_COLOR),
+                };
+                let delete = if state.confirm_delete.as_ref() == Some(&name) {
+                    "Confirm"
+                } else {
+                    "Delete"
+                };
+                parent.spawn((
+                    Node {
+                        column_gap: Val::Px(6.),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    children![
+                        (
+                            thumbnail,
+                            LevelThumbnail(name.clone()),
+                            Node {
+                                width: Val::Px(132.),
+                                height: Val::Px(60.),
+                                ..default()
+                            },
+                        ),
+                        (
+                            Text::new(name.clone()),
+                            TextFont::from_font_size(18.),
+                            LevelName(name.clone()),
+                            Node {
+                                flex_grow: 1.,
+                                ..default()
+                            },
+                        ),
+                        browser_button(BrowserButton::Load(name.clone()), "Load"),
+                        browser_button(BrowserButton::Duplicate(name.clone()), "Copy"),
+                        browser_button(BrowserButton::Rename(name.clone()), "Rename"),
+                        browser_button(BrowserButton::Export(name.clone()), "Export"),
+                        browser_button(BrowserButton::Delete(name), delete),
+                    ],
+                ));
+            }
+        });
+}
+fn browser_button(button: BrowserButton, text: impl Into<String>) -> impl Bundle {
+    (
+        Button,
+        button,
+        Text::new(text),
+        TextFont::from_font_size(18.),
+        BackgroundColor(NORMAL_BUTTON),
+        Outline::new(Val::Px(2.0), Val::ZERO, CRIMSON.into()),
+        Node {
+            min_height: Val::Px(24.),
+            padding: UiRect::horizontal(Val::Px(4.)),
+            ..default()
+        },
+    )
+}
+fn browser_button_system(
+    mut commands: Commands,
+    mut buttons: Query<(&Interaction, &BrowserButton, &mut Outline), Changed<Interaction>>,
+    library: Res<LevelLibrary>,
+    mut state: ResMut<BrowserState>,
+    mut indices: ResMut<Assets<LevelIndex>>,
+    save_files: Res<Assets<SaveFile>>,
+    mut editor_meta: ResMut<EditorMeta>,
+    mut pending_import: ResMut<PendingImport>,
+    mut event_writer: EventWriter<EditorEvents>,
+) {
+    for (interaction, button, mut outline) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => outline.color = PRESSED_BUTTON,
+            Interaction::Hovered => {
+                outline.color = HOVERED_BUTTON;
+                continue;
+            }
+            Interaction::None => {
+                outline.color = CRIMSON.into();
+                continue;
+            }
+        }
+        let confirm_delete = state.confirm_delete.take();
+        match button {
+            BrowserButton::Load(name) => {
+                event_writer.write(EditorEvents::LoadLevel {
+                    name: Some(name.clone()),
+                });
+                commands.trigger(UiRespawnTrigger::LevelBrowserToggle);
+            }
+            BrowserButton::OpenFile => {
+                event_writer.write(EditorEvents::LoadLevel { name: None });
+                commands.trigger(UiRespawnTrigger::LevelBrowserToggle);
+            }
+            BrowserButton::New => {
+                let result = update_index(&mut indices, &library.index, |index| {
+                    let name = unique_name(index, "new_level");
+                    io::write_level(&name, &SaveFile::default())?;
+                    index.levels.push(name.clone());
+                    Ok(name)
+                });
+                match result {
+                    Ok(name) => state.renaming = Some((name.clone(), name)),
+                    Err(err) => {
+                        commands.trigger(Notification::error(format!(
+                            "Could not create a level: {err}"
+                        )));
+                    }
+                }
+            }
+            BrowserButton::Duplicate(name) => {
+                let level = library
+                    .levels
+                    .get(name)
+                    .and_then(|handle| save_files.get(handle.id()));
+                let Some(level) = level else {
+                    commands.trigger(Notification::error(format!(
+                        "{name} is not loaded yet and can not be duplicated"
+                    )));
+                    continue;
+                };
+                let result = update_index(&mut indices, &library.index, |index| {
+                    let copy = unique_name(index, &format!("{name}_copy"));
+                    io::write_level(&copy, level)?;
+                    let pos = index
+                        .levels
+                        .iter()
+                        .position(|level| level == name)
+                        .map_or(index.levels.len(), |pos| pos + 1);
+                    index.levels.insert(pos, copy);
+                    Ok(())
+                });
+                if let Err(err) = result {
+                    commands.trigger(Notification::error(format!(
+                        "Could not duplicate {name}: {err}"
+                    )));
+                }
+            }
+            BrowserButton::Rename(name) => state.renaming = Some((name.clone(), name.clone())),
+            BrowserButton::Export(name) => {
+                let level = library
+                    .levels
+                    .get(name)
+                    .and_then(|handle| save_files.get(handle.id()));
+                let Some(level) = level else {
+                    commands.trigger(Notification::error(format!(
+                        "{name} is not loaded yet and can not be exported"
+                    )));
+                    continue;
+                };
+                let content = match io::save_file_to_string(level) {
+                    Ok(content) => content,
+                    Err(err) => {
+                        commands.trigger(Notification::error(format!(
+                            "Could not export {name}: {err}"
+                        )));
+                        continue;
+                    }
+                };
+                let file_name = format!("{name}.ron");
+                // on the web the browser asks where to save the download once it is written
+                IoTaskPool::get()
+                    .spawn(async move {
+                        let dialog = rfd::AsyncFileDialog::new().set_file_name(&file_name);
+                        let Some(file) = dialog.save_file().await else {
+                            return;
+                        };
+                        if let Err(err) = file.write(content.as_bytes()).await {
+                            warn!("could not export {file_name}: {err}");
+                        }
+                    })
+                    .detach();
+            }
+            BrowserButton::Import => {
+                let task = IoTaskPool::get().spawn(async {
+                    let file = rfd::AsyncFileDialog::new()
+                        .add_filter("level", &["ron"])
+                        .pick_file()
+                        .await?;
+                    Some((file.file_name(), file.read().await))
+                });
+                pending_import.0 = Some(task);
+            }
+            BrowserButton::Delete(name) if confirm_delete.as_ref() == Some(name) => {
+                let result = update_index(&mut indices, &library.index, |index| {
+                    io::delete_level(name)?;
+                    index.levels.retain(|level| level != name);
+                    Ok(())
+                });
+                if result.is_ok() && editor_meta.current_level_name.as_ref() == Some(name) {
+                    // the level is saved as a new file the next time
+                    editor_meta.level_path = None;
+                }
+                if let Err(err) = result {
+                    commands.trigger(Notification::error(format!(
+                        "Could not delete {name}: {err}"
+                    )));
+                }
+            }
+            BrowserButton::Delete(name) => {
+                state.confirm_delete = Some(name.clone());
+                commands.trigger(UiRespawnTrigger::LevelBrowserRespawn);
+            }
+        }
+        if confirm_delete.is_some() && state.confirm_delete.is_none() {
+            commands.trigger(UiRespawnTrigger::LevelBrowserRespawn);
+        }
+    }
+}
+/// Applies `change` to a copy of the index and stores it.
+/// The index is only replaced if `change` and writing the index succeeded
+fn update_index<T>(
+    indices: &mut Assets<LevelIndex>,
+    handle: &Handle<LevelIndex>,
+    change: impl FnOnce(&mut LevelIndex) -> Result<T, SaveError>,
+) -> Result<T, SaveError> {
+    let mut index = indices
+        .get(handle.id())
+        .cloned()
+        .ok_or(std::io::Error::from(std::io::ErrorKind::NotFound))?;
+    let result = change(&mut index)?;
+    io::write_level_index(&index)?;
+    indices.insert(handle.id(), index);
+    Ok(result)
+}
+/// `base` or `base` with the first number that is not in the index yet
+fn unique_name(index: &LevelIndex, base: &str) -> String {
+    std::iter::once(base.to_string())
+        .chain((2..).map(|i| format!("{base}_{i}")))
+        .find(|name| !index.levels.contains(name))
+        .unwrap()
+}
+/// Level names become file names, see [`io::level_path`]
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+fn check_level_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(is_name_char) {
+        return Err(format!(
+            "'{name}' may only contain letters, digits, '_' and '-'"
+        ));
+    }
+    Ok(())
+}
+fn type_level_name(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut state: ResMut<BrowserState>,
+    mut library: ResMut<LevelLibrary>,
+    mut indices: ResMut<Assets<LevelIndex>>,
+    save_files: Res<Assets<SaveFile>>,
+    mut editor_meta: ResMut<EditorMeta>,
+) {
+    let Some((_, buffer)) = &mut state.renaming else {
+        keyboard_events.clear();
+        return;
+    };
+    let Some(confirmed) = read_typed_name(&mut keyboard_events, buffer) else {
+        return;
+    };
+    let Some((from, to)) = state.renaming.take() else {
+        return;
+    };
+    let to = to.trim().to_string();
+    if !confirmed || to == from {
+        return;
+    }
+    if let Err(err) = check_level_name(&to) {
+        commands.trigger(Notification::error(format!(
+            "{from} was not renamed: {err}"
+        )));
+        return;
+    }
+    let level = library
+        .levels
+        .get(&from)
+        .and_then(|handle| save_files.get(handle.id()));
+    let Some(level) = level else {
+        commands.trigger(Notification::error(format!(
+            "{from} is not loaded yet, confirm the name again in a moment"
+        )));
+        // keep the typed name so the rename can be confirmed again
+        state.renaming = Some((from, to));
+        return;
+    };
+    let result = update_index(&mut indices, &library.index, |index| {
+        if index.levels.contains(&to) {
+            return Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists).into());
+        }
+        io::rename_level(&from, &to, level)?;
+        for level in &mut index.levels {
+            if *level == from {
+                level.clone_from(&to);
+            }
+        }
+        Ok(())
+    });
+    if let Err(err) = result {
+        commands.trigger(Notification::error(format!(
+            "Could not rename {from} to {to}: {err}"
+        )));
+        return;
+    }
+    library.levels.remove(&from);
+    if let Some(thumbnail) = library.thumbnails.remove(&from) {
+        library.thumbnails.insert(to.clone(), thumbnail);
+    }
+    if editor_meta.current_level_name.as_ref() == Some(&from) {
+        editor_meta.level_path = Some(io::level_path(&to));
+        editor_meta.current_level_name = Some(to);
+    }
+}
+/// Applies the typed keys to `buffer`.
+/// [`Some`] once the name is confirmed with `Enter` (`true`) or discarded with `Escape` (`false`)
+fn read_typed_name(
+    keyboard_events: &mut EventReader<KeyboardInput>,
+    buffer: &mut String,
+) -> Option<bool> {
+    let mut finished = None;
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Backspace => {
+                buffer.pop();
+            }
+            Key::Enter => finished = Some(true),
+            Key::Escape => finished = Some(false),
+            _ => {
+                if let Some(text) = &event.text {
+                    buffer.extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+    }
+    finished
+}
+fn open_save_prompt(
+    trigger: Trigger<UiRespawnTrigger>,
+    mut commands: Commands,
+    mut prompt: ResMut<SavePrompt>,
+    mut state: ResMut<BrowserState>,
+    editor_meta: Res<EditorMeta>,
+) {
+    if !matches!(trigger.event(), UiRespawnTrigger::SavePrompt) || prompt.0.is_some() {
+        return;
+    }
+    state.renaming = None;
+    let name = editor_meta
+        .current_level_name
+        .clone()
+        .unwrap_or_else(|| "level".into());
+    commands.spawn((
+        Text::new(format!("Save as {name}_")),
+        TextFont::from_font_size(18.),
+        Node {
+            left: Val::Percent(35.),
+            top: Val::Percent(40.),
+            width: Val::Percent(30.),
+            padding: UiRect::all(Val::Px(8.)),
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        BackgroundColor(DEAD_BACKGROUND.with_alpha(0.9)),
+        SavePromptUiRoot,
+    ));
+    prompt.0 = Some(name);
+}
+/// Writes the level under the typed name once it is confirmed and adds it to the index
+fn type_save_name(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut prompt: ResMut<SavePrompt>,
+    mut prompt_texts: Query<(Entity, &mut Text), With<SavePromptUiRoot>>,
+    library: Res<LevelLibrary>,
+    mut indices: ResMut<Assets<LevelIndex>>,
+    level_tiles: LevelTiles,
+    mut editor_meta: ResMut<EditorMeta>,
+) {
+    let Some(buffer) = &mut prompt.0 else {
+        keyboard_events.clear();
+        return;
+    };
+    let finished = read_typed_name(&mut keyboard_events, buffer);
+    let content = format!("Save as {buffer}_");
+    for (_, mut text) in &mut prompt_texts {
+        if text.0 != content {
+            text.0.clone_from(&content);
+        }
+    }
+    let Some(confirmed) = finished else {
+        return;
+    };
+    for (e, _) in &prompt_texts {
+        commands.entity(e).despawn();
+    }
+    let Some(name) = prompt.0.take() else {
+        return;
+    };
+    let name = name.trim();
+    if !confirmed {
+        info!("Save was aborted since no name was typed");
+        return;
+    }
+    if let Err(err) = check_level_name(name) {
+        commands.trigger(Notification::error(format!(
+            "The level was not saved: {err}"
+        )));
+        return;
+    }
+    let mut level = level_tiles.to_save_file();
+    level.meta = editor_meta.level_meta.clone();
+    level.prefabs = editor_meta.prefab_instances.clone();
+    let current = editor_meta.current_level_name.as_deref() == Some(name);
+    let result = update_index(&mut indices, &library.index, |index| {
+        let listed = index.levels.iter().any(|level| level == name);
+        // only the level itself is overwritten
+        if listed && !current {
+            return Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists).into());
+        }
+        io::write_level(name, &level)?;
+        if !listed {
+            index.levels.push(name.to_string());
+        }
+        Ok(())
+    });
+    match result {
+        Ok(()) => {
+            commands.trigger(Notification::info(format!("Saved {name}")));
+            editor_meta.level_path = Some(io::level_path(name));
+            editor_meta.current_level_name = Some(name.to_string());
+        }
+        Err(err) => commands.trigger(Notification::error(format!("Could not save {name}: {err}"))),
+    }
+}
+fn update_name_texts(mut names: Query<(&LevelName, &mut Text)>, state: Res<BrowserState>) {
+    for (name, mut text) in &mut names {
+        let content = match &state.renaming {
+            Some((renamed, buffer)) if *renamed == name.0 => format!("{buffer}_"),
+            _ => name.0.clone(),
+        };
+        if text.0 != content {
+            text.0 = content;
+        }
+    }
+}
+/// Renders the thumbnails of the listed levels that are loaded and shows them.
+/// Levels that changed on disk are rendered again
+fn update_thumbnails(
+    mut library: ResMut<LevelLibrary>,
+    mut level_events: EventReader<AssetEvent<SaveFile>>,
+    save_files: Res<Assets<SaveFile>>,
+    textures: Res<map::Textures>,
+    mut images: ResMut<Assets<Image>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut atlases: Local<Option<HashMap<LayerType, TileAtlas>>>,
+    mut thumbnails: Query<(&LevelThumbnail, &mut ImageNode)>,
+) {
+    for event in level_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        let LevelLibrary {
+            levels,
+            thumbnails: rendered,
+            ..
+        } = library.as_mut();
+        rendered.retain(|name, _| levels.get(name).is_none_or(|level| level.id() != *id));
+    }
+    if thumbnails.is_empty() {
+        return;
+    }
+    if atlases.is_none() {
+        *atlases = Some(tile_atlases(&textures, &images, &layouts))
+            .filter(|atlases| !atlases.is_empty() && atlases.len() == textures.pack.len());
+    }
+    let Some(atlases) = atlases.as_ref() else {
+        return;
+    };
+    for (LevelThumbnail(name), mut image_node) in &mut thumbnails {
+        if !library.thumbnails.contains_key(name) {
+            let level = library
+                .levels
+                .get(name)
+                .and_then(|handle| save_files.get(handle.id()));
+            let Some(level) = level else {
+                continue;
+            };
+            let image = Image::from_dynamic(
+                DynamicImage::ImageRgba8(thumbnail::render_level(level, atlases)),
+                true,
+                RenderAssetUsages::RENDER_WORLD,
+            );
+            let handle = images.add(image);
+            library.thumbnails.insert(name.clone(), handle);
+        }
+        let handle = &library.thumbnails[name];
+        if image_node.image != *handle {
+            image_node.image = handle.clone();
+            image_node.color = Color::WHITE;
+        }
+    }
+}
+/// Copies the tileset images of every layer to the CPU for [`thumbnail::render_level`]
+fn tile_atlases(
+    textures: &map::Textures,
+    images: &Assets<Image>,
+    layouts: &Assets<TextureAtlasLayout>,
+) -> HashMap<LayerType, TileAtlas> {
+    textures
+        .pack
+        .iter()
+        .filter_map(|(layer_type, pack)| {
+            let image = images.get(pack.texture.id())?.clone();
+            let image = image.try_into_dynamic().ok()?.into_rgba8();
+            let cells = layouts.get(pack.layout.id())?.size / TILESIZE as u32;
+            Some((*layer_type, TileAtlas::new(image, cells.x, cells.y)))
+        })
+        .collect()
+}
+/// Adds the picked file to the index once the dialog of [`BrowserButton::Import`] is closed
+fn finish_import(
+    mut commands: Commands,
+    mut pending_import: ResMut<PendingImport>,
+    library: Res<LevelLibrary>,
+    mut indices: ResMut<Assets<LevelIndex>>,
+) {
+    let Some(task) = &mut pending_import.0 else {
+        return;
+    };
+    let Some(picked) = check_ready(task) else {
+        return;
+    };
+    pending_import.0 = None;
+    let Some((file_name, bytes)) = picked else {
+        return;
+    };
+    let level = match io::parse_save_file(&bytes) {
+        Ok(level) => level,
+        Err(err) => {
+            commands.trigger(Notification::error(format!(
+                "Could not import {file_name}: {err}"
+            )));
+            return;
+        }
+    };
+    let name: String = file_name
+        .trim_end_matches(".ron")
+        .chars()
+        .map(|c| if is_name_char(c) { c } else { '_' })
+        .collect();
+    let result = update_index(&mut indices, &library.index, |index| {
+        let name = unique_name(index, if name.is_empty() { "imported" } else { &name });
+        io::write_level(&name, &level)?;
+        index.levels.push(name.clone());
+        Ok(name)
+    });
+    match result {
+        Ok(name) => commands.trigger(Notification::info(format!("Imported {name}"))),
+        Err(err) => commands.trigger(Notification::error(format!(
+            "Could not import {file_name}: {err}"
+        ))),
+    }
+}
+/// Lists added, renamed and deleted levels
+fn refresh_level_browser(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<LevelIndex>>,
+    panels: Query<(), With<LevelBrowserUiRoot>>,
+) {
+    if events.read().count() > 0 && !panels.is_empty() {
+        commands.trigger(UiRespawnTrigger::LevelBrowserRespawn);
+    }
+}
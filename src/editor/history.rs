@@ -0,0 +1,172 @@
+//! Undo and redo of tile edits. Ctrl+Z undoes the last edit, Ctrl+Shift+Z redoes it.
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    io::{PrefabInstance, Tile},
+    map::LayerType,
+};
+
+use super::{EditorEvents, EditorMeta, browser};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<EditHistory>().add_systems(
+        Update,
+        check_shortcuts.run_if(
+            in_state(GameState::Running)
+                .and(|meta: Res<EditorMeta>| meta.edit_mode)
+                .and(browser::not_typing),
+        ),
+    );
+}
+/// One edit of the tiles of a single layer
+pub struct TileEdit {
+    pub layer_type: LayerType,
+    /// Tiles that were removed by the edit
+    pub before: Vec<Tile>,
+    /// Tiles that were placed by the edit
+    pub after: Vec<Tile>,
+}
+impl TileEdit {
+    /// Every position touched by the edit
+    pub fn positions(&self) -> impl Iterator<Item = UVec2> {
+        self.before.iter().chain(&self.after).map(|tile| tile.pos)
+    }
+}
+/// Edits that are undone together, like pasting into several layers
+pub struct Change {
+    pub edits: Vec<TileEdit>,
+    /// Prefab that was placed by the change
+    pub prefab: Option<PrefabInstance>,
+    /// Linked prefabs that the change unlinked by editing their tiles
+    pub unlinked: Vec<PrefabInstance>,
+}
+/// Changes of the current level. Cleared when another level is loaded
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+impl EditHistory {
+    /// Records a new edit. Edits that were undone before can not be redone afterwards
+    pub fn push(&mut self, edit: TileEdit, unlinked: Vec<PrefabInstance>) {
+        self.push_change(Change {
+            edits: vec![edit],
+            prefab: None,
+            unlinked,
+        });
+    }
+    pub fn push_change(&mut self, mut change: Change) {
+        change
+            .edits
+            .retain(|edit| !edit.before.is_empty() || !edit.after.is_empty());
+        if change.edits.is_empty() && change.prefab.is_none() {
+            return;
+        }
+        self.undo.push(change);
+        self.redo.clear();
+    }
+    /// The change that has to be reverted
+    pub fn undo(&mut self) -> Option<&Change> {
+        let change = self.undo.pop()?;
+        self.redo.push(change);
+        self.redo.last()
+    }
+    /// The change that has to be applied again
+    pub fn redo(&mut self) -> Option<&Change> {
+        let change = self.redo.pop()?;
+        self.undo.push(change);
+        self.undo.last()
+    }
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+fn check_shortcuts(keys: Res<ButtonInput<KeyCode>>, mut event_writer: EventWriter<EditorEvents>) {
+    if !keys.just_pressed(KeyCode::KeyZ)
+        || !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
+        return;
+    }
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        event_writer.write(EditorEvents::Redo);
+    } else {
+        event_writer.write(EditorEvents::Undo);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(index: usize) -> TileEdit {
+        TileEdit {
+            layer_type: LayerType::Fg,
+            before: Vec::new(),
+            after: vec![Tile {
+                pos: UVec2::new(1, 2),
+                index,
+                ..default()
+            }],
+        }
+    }
+    /// Index of the tile placed by the last edit of `change`
+    fn index(change: Option<&Change>) -> Option<usize> {
+        Some(change?.edits.last()?.after.last()?.index)
+    }
+    #[test]
+    fn drops_empty_edits() {
+        let mut history = EditHistory::default();
+        history.push(
+            TileEdit {
+                layer_type: LayerType::Fg,
+                before: Vec::new(),
+                after: Vec::new(),
+            },
+            Vec::new(),
+        );
+        assert!(history.undo().is_none());
+    }
+    #[test]
+    fn redoes_the_undone_change() {
+        let mut history = EditHistory::default();
+        history.push(edit(1), Vec::new());
+        history.push(edit(2), Vec::new());
+        assert_eq!(index(history.undo()), Some(2));
+        assert_eq!(index(history.redo()), Some(2));
+        assert!(history.redo().is_none());
+        assert_eq!(index(history.undo()), Some(2));
+        assert_eq!(index(history.undo()), Some(1));
+        assert!(history.undo().is_none());
+    }
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut history = EditHistory::default();
+        history.push(edit(1), Vec::new());
+        history.undo();
+        history.push(edit(2), Vec::new());
+        assert!(history.redo().is_none());
+        assert_eq!(index(history.undo()), Some(2));
+        assert!(history.undo().is_none());
+    }
+    #[test]
+    fn keeps_unlinked_prefabs() {
+        let mut history = EditHistory::default();
+        let prefab = PrefabInstance {
+            path: "prefabs/tower_pit.prefab.ron".into(),
+            pos: UVec2::new(0, 1),
+            size: UVec2::new(4, 4),
+            linked: true,
+        };
+        history.push(edit(1), vec![prefab.clone()]);
+        assert_eq!(
+            history.undo().map(|change| &change.unlinked),
+            Some(&vec![prefab.clone()])
+        );
+        assert_eq!(
+            history.redo().map(|change| &change.unlinked),
+            Some(&vec![prefab])
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Panel below the overview to pick the edited layer and to hide or lock layers.
+//!
+//! The state of each layer is stored on its tilemap, see [`LayerControls`].
+//! Hidden and dimmed layers are only applied in edit mode, the game always shows every layer.
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::{TileColor, TileStorage};
+
+use super::{EditorMeta, NORMAL_BUTTON, PRESSED_BUTTON, UiRespawnTrigger};
+use crate::{map::LayerType, screens::GameState, widget::DEAD_BACKGROUND};
+
+/// Alpha of the layers that are not edited while [`EditorMeta::dim_inactive_layers`] is set
+const DIMMED_ALPHA: f32 = 0.35;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_required_components::<TileStorage, LayerControls>()
+        .add_observer(toggle_layer_panel)
+        .add_systems(
+            Update,
+            apply_layer_controls.run_if(in_state(GameState::Running)),
+        )
+        .add_systems(
+            Update,
+            layer_button_system
+                .run_if(in_state(GameState::Running).and(|meta: Res<EditorMeta>| meta.edit_mode)),
+        );
+}
+/// Editor state of a layer, stored on the tilemap of the layer
+#[derive(Component, Default)]
+pub struct LayerControls {
+    pub hidden: bool,
+    /// Locked layers are not changed by painting, erasing or pasting
+    pub locked: bool,
+}
+#[derive(Component)]
+struct LayerPanelUiRoot;
+#[derive(Component)]
+enum LayerButton {
+    Select(LayerType),
+    Hide(LayerType),
+    Lock(LayerType),
+    Dim,
+}
+fn toggle_layer_panel(
+    trigger: Trigger<UiRespawnTrigger>,
+    mut commands: Commands,
+    editor_meta: Res<EditorMeta>,
+    layers: Query<(&LayerType, &LayerControls)>,
+    panels: Query<Entity, With<LayerPanelUiRoot>>,
+) {
+    let close = match trigger.event() {
+        UiRespawnTrigger::TileSelectionRespawn | UiRespawnTrigger::LayerPanelRespawn => false,
+        UiRespawnTrigger::TileSelectionRemove => true,
+        _ => return,
+    };
+    for e in &panels {
+        commands.entity(e).despawn();
+    }
+    if close {
+        return;
+    }
+    commands
+        .spawn((
+            Node {
+                left: Val::Percent(20.),
+                top: Val::Percent(5.),
+                padding: UiRect::all(Val::Px(6.)),
+                row_gap: Val::Px(6.),
+                flex_direction: FlexDirection::Column,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            BackgroundColor(DEAD_BACKGROUND.with_alpha(0.8)),
+            LayerPanelUiRoot,
+        ))
+        .with_children(|parent| {
+            for layer_type in (0..3).map(LayerType::from_u8) {
+                let Some((_, controls)) = layers.iter().find(|(layer, _)| **layer == layer_type)
+                else {
+                    continue;
+                };
+                let hide = if controls.hidden { "Show" } else { "Hide" };
+                let lock = if controls.locked { "Unlock" } else { "Lock" };
+                parent.spawn((
+                    Node {
+                        column_gap: Val::Px(8.),
+                        ..default()
+                    },
+                    children![
+                        layer_button(
+                            LayerButton::Select(layer_type),
+                            layer_type.name(),
+                            editor_meta.layer_type == layer_type
+                        ),
+                        layer_button(LayerButton::Hide(layer_type), hide, controls.hidden),
+                        layer_button(LayerButton::Lock(layer_type), lock, controls.locked),
+                    ],
+                ));
+            }
+            parent.spawn(layer_button(
+                LayerButton::Dim,
+                "Dim others",
+                editor_meta.dim_inactive_layers,
+            ));
+        });
+}
+/// Buttons that are switched on keep the pressed outline
+fn layer_button(button: LayerButton, text: &str, active: bool) -> impl Bundle {
+    let color = if active {
+        PRESSED_BUTTON
+    } else {
+        NORMAL_BUTTON
+    };
+    (
+        Button,
+        button,
+        Text::new(text),
+        TextFont::from_font_size(18.),
+        BackgroundColor(DEAD_BACKGROUND),
+        Outline::new(Val::Px(2.0), Val::ZERO, color),
+        Node {
+            padding: UiRect::horizontal(Val::Px(4.)),
+            ..default()
+        },
+    )
+}
+fn layer_button_system(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &LayerButton), Changed<Interaction>>,
+    mut layers: Query<(&LayerType, &mut LayerControls)>,
+    mut editor_meta: ResMut<EditorMeta>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let mut change_layer = |layer_type: LayerType, change: fn(&mut LayerControls)| {
+            for (layer, mut controls) in &mut layers {
+                if *layer == layer_type {
+                    change(&mut controls);
+                }
+            }
+        };
+        match button {
+            LayerButton::Select(layer_type) => {
+                editor_meta.layer_type = *layer_type;
+                editor_meta.autotile = None;
+                commands.trigger(UiRespawnTrigger::OverviewRespawn);
+                commands.trigger(UiRespawnTrigger::TileSelectionRespawn);
+                continue;
+            }
+            LayerButton::Hide(layer_type) => {
+                change_layer(*layer_type, |controls| controls.hidden = !controls.hidden);
+            }
+            LayerButton::Lock(layer_type) => {
+                change_layer(*layer_type, |controls| controls.locked = !controls.locked);
+            }
+            LayerButton::Dim => {
+                editor_meta.dim_inactive_layers = !editor_meta.dim_inactive_layers;
+            }
+        }
+        commands.trigger(UiRespawnTrigger::LayerPanelRespawn);
+    }
+}
+/// Hides and dims the tiles and sprites of each layer
+fn apply_layer_controls(
+    editor_meta: Res<EditorMeta>,
+    mut tilemaps: Query<(&LayerType, &LayerControls, &mut Visibility)>,
+    mut tiles: Query<(&LayerType, &mut TileColor)>,
+    mut sprites: Query<(&mut Sprite, Option<&LayerType>, Option<&ChildOf>)>,
+    layer_types: Query<&LayerType>,
+) {
+    let mut alphas = HashMap::new();
+    for (layer_type, controls, mut visibility) in &mut tilemaps {
+        let active = editor_meta.layer_type == *layer_type;
+        let alpha = if !editor_meta.edit_mode {
+            1.
+        } else if controls.hidden {
+            0.
+        } else if editor_meta.dim_inactive_layers && !active {
+            DIMMED_ALPHA
+        } else {
+            1.
+        };
+        visibility.set_if_neq(if alpha == 0. {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+        alphas.insert(*layer_type, alpha);
+    }
+    for (layer_type, mut color) in &mut tiles {
+        let alpha = alphas.get(layer_type).copied().unwrap_or(1.);
+        if color.0.alpha() != alpha {
+            color.0.set_alpha(alpha);
+        }
+    }
+    // players and enemies are drawn outside of the tilemap, the fire of towers is a child of its tile
+    for (mut sprite, layer_type, parent) in &mut sprites {
+        let layer_type = layer_type.or_else(|| layer_types.get(parent?.0).ok());
+        let Some(alpha) = layer_type.and_then(|layer_type| alphas.get(layer_type)) else {
+            continue;
+        };
+        if sprite.color.alpha() != *alpha {
+            sprite.color.set_alpha(*alpha);
+        }
+    }
+}
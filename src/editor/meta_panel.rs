@@ -0,0 +1,268 @@
+//! Panel for editing the [`LevelMeta`] of the current level.
+//!
+//! Click a value to edit it, type and confirm with `Enter` or discard with `Escape`.
+//! Optional values are cleared by confirming an empty text.
+//! The map size is entered as `width`x`height` and keeps all tiles that still fit.
+
+use bevy::{
+    color::palettes::css::CRIMSON,
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+
+use super::{
+    EditorEvents, EditorMeta, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON, UiRespawnTrigger,
+};
+use crate::{io::LevelMeta, map::MapSize, screens::GameState, widget::DEAD_BACKGROUND};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<EditedField>()
+        .add_observer(toggle_meta_panel)
+        .add_systems(
+            Update,
+            (
+                meta_field_button_system,
+                type_into_field,
+                update_field_texts,
+            )
+                .chain()
+                .run_if(in_state(GameState::Running).and(|meta: Res<EditorMeta>| meta.edit_mode)),
+        );
+}
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum MetaField {
+    Name,
+    Author,
+    ParTime,
+    ParDeaths,
+    Music,
+    TowerCountdown,
+    Next,
+    MapSize,
+}
+impl MetaField {
+    const ALL: [MetaField; 8] = [
+        MetaField::Name,
+        MetaField::Author,
+        MetaField::ParTime,
+        MetaField::ParDeaths,
+        MetaField::Music,
+        MetaField::TowerCountdown,
+        MetaField::Next,
+        MetaField::MapSize,
+    ];
+    fn label(&self) -> &'static str {
+        match self {
+            MetaField::Name => "Name",
+            MetaField::Author => "Author",
+            MetaField::ParTime => "Par time (s)",
+            MetaField::ParDeaths => "Par deaths",
+            MetaField::Music => "Music",
+            MetaField::TowerCountdown => "Tower countdown (s)",
+            MetaField::Next => "Next level",
+            MetaField::MapSize => "Map size",
+        }
+    }
+    fn read(&self, meta: &LevelMeta, map_size: &MapSize) -> String {
+        fn optional(value: Option<impl ToString>) -> String {
+            value.map(|value| value.to_string()).unwrap_or_default()
+        }
+        match self {
+            MetaField::Name => meta.name.clone(),
+            MetaField::Author => meta.author.clone(),
+            MetaField::ParTime => optional(meta.par_time),
+            MetaField::ParDeaths => optional(meta.par_deaths),
+            MetaField::Music => optional(meta.music.as_ref()),
+            MetaField::TowerCountdown => optional(meta.tower_countdown),
+            MetaField::Next => optional(meta.next.as_ref()),
+            MetaField::MapSize => format!("{}x{}", map_size.0.x, map_size.0.y),
+        }
+    }
+    fn write(&self, meta: &mut LevelMeta, value: &str) -> Result<(), String> {
+        fn optional<T: std::str::FromStr>(value: &str) -> Result<Option<T>, String> {
+            if value.is_empty() {
+                return Ok(None);
+            }
+            value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("'{value}' is not a valid number"))
+        }
+        let value = value.trim();
+        match self {
+            MetaField::Name => meta.name = value.into(),
+            MetaField::Author => meta.author = value.into(),
+            MetaField::ParTime => meta.par_time = optional(value)?,
+            MetaField::ParDeaths => meta.par_deaths = optional(value)?,
+            MetaField::Music => meta.music = (!value.is_empty()).then(|| value.into()),
+            MetaField::TowerCountdown => meta.tower_countdown = optional(value)?,
+            MetaField::Next => meta.next = (!value.is_empty()).then(|| value.into()),
+            // applied through [`EditorEvents::ResizeMap`]
+            MetaField::MapSize => {}
+        }
+        Ok(())
+    }
+}
+fn parse_map_size(value: &str) -> Result<UVec2, String> {
+    let invalid = || format!("'{value}' is not a size like 44x20");
+    let (width, height) = value.trim().split_once('x').ok_or_else(invalid)?;
+    let width = width.trim().parse::<u32>().map_err(|_| invalid())?;
+    let height = height.trim().parse::<u32>().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok(UVec2::new(width, height))
+}
+/// The field that currently receives keyboard input together with the typed text
+#[derive(Resource, Default)]
+struct EditedField(Option<(MetaField, String)>);
+
+#[derive(Component)]
+struct MetaPanelUiRoot;
+fn toggle_meta_panel(
+    trigger: Trigger<UiRespawnTrigger>,
+    mut commands: Commands,
+    mut edited_field: ResMut<EditedField>,
+    editor_meta: Res<EditorMeta>,
+    map_size: Res<MapSize>,
+    panels: Query<Entity, With<MetaPanelUiRoot>>,
+) {
+    let close = match trigger.event() {
+        UiRespawnTrigger::LevelMetaToggle => !panels.is_empty(),
+        UiRespawnTrigger::TileSelectionRemove => true,
+        _ => return,
+    };
+    edited_field.0 = None;
+    for e in &panels {
+        commands.entity(e).despawn();
+    }
+    if close {
+        return;
+    }
+    commands
+        .spawn((
+            Node {
+                left: Val::Percent(1.),
+                top: Val::Percent(20.),
+                width: Val::Percent(25.),
+                padding: UiRect::all(Val::Px(8.)),
+                row_gap: Val::Px(6.),
+                flex_direction: FlexDirection::Column,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            BackgroundColor(DEAD_BACKGROUND.with_alpha(0.8)),
+            MetaPanelUiRoot,
+        ))
+        .with_children(|parent| {
+            for field in MetaField::ALL {
+                parent.spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    children![
+                        (
+                            Text::new(field.label()),
+                            TextFont::from_font_size(14.),
+                            TextColor(NORMAL_BUTTON),
+                        ),
+                        (
+                            Button,
+                            field,
+                            Text::new(field.read(&editor_meta.level_meta, &map_size)),
+                            TextFont::from_font_size(18.),
+                            BackgroundColor(NORMAL_BUTTON),
+                            Outline::new(Val::Px(2.0), Val::ZERO, CRIMSON.into()),
+                            Node {
+                                min_height: Val::Px(24.),
+                                padding: UiRect::horizontal(Val::Px(4.)),
+                                ..default()
+                            },
+                        ),
+                    ],
+                ));
+            }
+        });
+}
+fn meta_field_button_system(
+    mut fields: Query<(&Interaction, &MetaField, &mut Outline), Changed<Interaction>>,
+    editor_meta: Res<EditorMeta>,
+    map_size: Res<MapSize>,
+    mut edited_field: ResMut<EditedField>,
+) {
+    for (interaction, field, mut outline) in &mut fields {
+        match interaction {
+            Interaction::Pressed => {
+                outline.color = PRESSED_BUTTON;
+                edited_field.0 = Some((*field, field.read(&editor_meta.level_meta, &map_size)));
+            }
+            Interaction::Hovered => outline.color = HOVERED_BUTTON,
+            Interaction::None => outline.color = CRIMSON.into(),
+        }
+    }
+}
+fn type_into_field(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut edited_field: ResMut<EditedField>,
+    mut editor_meta: ResMut<EditorMeta>,
+    mut event_writer: EventWriter<EditorEvents>,
+) {
+    let Some((field, buffer)) = &mut edited_field.0 else {
+        keyboard_events.clear();
+        return;
+    };
+    let field = *field;
+    let mut finished = false;
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Backspace => {
+                buffer.pop();
+            }
+            Key::Enter => {
+                let result = if field == MetaField::MapSize {
+                    parse_map_size(buffer).map(|size| {
+                        event_writer.write(EditorEvents::ResizeMap(size));
+                    })
+                } else {
+                    field.write(&mut editor_meta.level_meta, buffer)
+                };
+                if let Err(err) = result {
+                    warn!("{} was not changed: {err}", field.label());
+                }
+                finished = true;
+            }
+            Key::Escape => finished = true,
+            _ => {
+                if let Some(text) = &event.text {
+                    buffer.extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+    }
+    if finished {
+        edited_field.0 = None;
+    }
+}
+fn update_field_texts(
+    mut fields: Query<(&MetaField, &mut Text)>,
+    editor_meta: Res<EditorMeta>,
+    map_size: Res<MapSize>,
+    edited_field: Res<EditedField>,
+) {
+    for (field, mut text) in &mut fields {
+        let content = match &edited_field.0 {
+            Some((edited, buffer)) if edited == field => format!("{buffer}_"),
+            _ => field.read(&editor_meta.level_meta, &map_size),
+        };
+        if text.0 != content {
+            text.0 = content;
+        }
+    }
+}
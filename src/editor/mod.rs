@@ -0,0 +1,1655 @@
+mod browser;
+mod history;
+mod layers;
+mod meta_panel;
+mod notification;
+mod playtest;
+mod prefabs;
+mod tools;
+mod validation;
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    time::Duration,
+};
+
+use avian2d::prelude::LinearVelocity;
+use bevy::{
+    asset::LoadState,
+    color::palettes::{self},
+    ecs::system::SystemParam,
+    input::{
+        common_conditions::input_just_released,
+        mouse::{MouseScrollUnit, MouseWheel},
+    },
+    picking::hover::HoverMap,
+    prelude::*,
+};
+use bevy_ecs_tilemap::{
+    map::{TilemapId, TilemapSize},
+    tiles::{TileBundle, TilePos, TileStorage, TileTextureIndex},
+};
+
+use history::{Change, EditHistory, TileEdit};
+use layers::LayerControls;
+use notification::Notification;
+use playtest::Playtest;
+use tools::{EditorTool, Stamp};
+
+use crate::{
+    GameState, MainCamera,
+    animation::{EnemyAnimation, PlayerAnimation},
+    autotile::AutotileGroup,
+    campaign::{Campaign, CampaignProgress},
+    combat::{ScreenShake, TRAUMA},
+    entity::{Enemy, OnSpawnTrigger, Player, PlayerMode, Rule, TileProperties, TowerCountdown},
+    io::{self, SaveFile, Tile},
+    map::{
+        self, BACKGROUND_COLOR, DEBUG_BACKGROUND_COLOR, LayerType, MapSize, MousePosition,
+        TILEMAP_OFFSET, TILESIZE, convert_to_tile_pos,
+    },
+    utils::{iter_grid_line, iter_grid_rect, tile_to_world},
+    widget::{self, DEAD_BACKGROUND},
+};
+pub const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+pub const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+pub const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+pub fn plugin(app: &mut App) {
+    app.add_event::<EditorEvents>()
+        .init_resource::<EditorMeta>()
+        .add_event::<UiRespawnTrigger>()
+        .add_observer(ui_tile_selection_update)
+        .add_observer(draw_deads)
+        .add_observer(init_ui_overview)
+        .add_observer(show_control_image)
+        .add_observer(show_level_title)
+        .add_plugins((
+            meta_panel::plugin,
+            history::plugin,
+            layers::plugin,
+            tools::plugin,
+            prefabs::plugin,
+            browser::plugin,
+            notification::plugin,
+            playtest::plugin,
+            validation::plugin,
+        ))
+        .add_systems(
+            Update,
+            (|mut commands: Commands| commands.trigger(UiRespawnTrigger::OverviewRespawn)).run_if(
+                input_just_released(KeyCode::KeyP)
+                    .and(in_state(GameState::Running))
+                    .and(browser::not_typing),
+            ),
+        )
+        .add_systems(OnEnter(GameState::Running), |mut commands: Commands| {
+            commands.trigger(UiRespawnTrigger::ShowControlImage);
+            commands.trigger(UiRespawnTrigger::Deads);
+        })
+        .add_systems(
+            Update,
+            (
+                debug.run_if(browser::not_typing),
+                update_control_images,
+                update_level_title,
+                process_editor_events,
+                overview_button_system,
+                check_level_loaded,
+                update_scroll_position,
+            )
+                .run_if(in_state(GameState::Running)),
+        )
+        .add_systems(
+            Update,
+            (
+                current_tile_ui,
+                tile_button_system,
+                autotile_button_system,
+                update_selected_tile,
+                draw_selection_indicator,
+                check_input,
+                check_save_shortcuts.run_if(browser::not_typing),
+            )
+                .run_if(in_state(GameState::Running).and(|meta: Res<EditorMeta>| meta.edit_mode)),
+        );
+}
+#[derive(Resource, Default)]
+pub struct EditorMeta {
+    selected_tile: Option<TextureAtlas>,
+    /// Index of the autotile group in the tileset of the current layer.
+    /// The brush places [`selected_tile`](Self::selected_tile) and picks the variants afterwards
+    autotile: Option<usize>,
+    /// Stores a started selection in world space.
+    /// You can select a tile region by pressing LMouse and dragging over a region
+    current_selection_start: Option<Vec2>,
+    /// Tiles collected by a [`EditorTool::is_stroke`] tool since the button was pressed
+    current_stroke: Vec<UVec2>,
+    layer_type: LayerType,
+    /// Draws the other layers transparent in edit mode, see [`layers`]
+    dim_inactive_layers: bool,
+    tool: EditorTool,
+    /// Region selected with [`EditorTool::Select`] in tile positions
+    selection: Option<URect>,
+    /// Tiles copied from the selection
+    clipboard: Option<Stamp>,
+    /// The clipboard follows the cursor and is placed on click
+    pasting: bool,
+    /// Places prefabs as plain tiles that do not follow changes to the prefab file
+    place_unlinked: bool,
+    pub current_level: Handle<SaveFile>,
+    /// Name of the current level as used in [`EditorEvents::LoadLevel`].
+    /// [`None`] if the level was loaded from outside of the level folder
+    pub current_level_name: Option<String>,
+    /// Asset path [`EditorEvents::SaveLevel`] writes to.
+    /// [`None`] for new levels and levels imported from other formats, they are saved with a file dialog
+    pub level_path: Option<PathBuf>,
+    /// Metadata of the current level. Edited in the level panel and written back on save
+    pub level_meta: io::LevelMeta,
+    /// Prefabs placed in the current level, written back on save
+    pub prefab_instances: Vec<io::PrefabInstance>,
+    /// [`Time::elapsed`] when the current level was loaded
+    pub level_started: Duration,
+    pub edit_mode: bool,
+    pub deads: usize,
+    /// Deaths since the current level was loaded
+    pub level_deads: usize,
+}
+fn check_input(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mouse_position: Res<MousePosition>,
+    mut event_writer: EventWriter<EditorEvents>,
+    mut editor_meta: ResMut<EditorMeta>,
+    ui_q: Query<&Interaction>,
+    map_size: Res<MapSize>,
+    level_tiles: LevelTiles,
+) {
+    let hovered_tile = mouse_position.to_tilepos(&map_size).map(UVec2::from);
+    if editor_meta.pasting {
+        let ui_interaction = ui_q
+            .iter()
+            .any(|interaction| *interaction != Interaction::None);
+        let clicked = mouse.just_pressed(MouseButton::Left) && !ui_interaction;
+        if let Some(tile) = hovered_tile.filter(|_| clicked) {
+            event_writer.write(EditorEvents::PasteStamp(tile));
+        }
+        return;
+    }
+    if mouse.just_pressed(MouseButton::Left) {
+        if ui_q
+            .iter()
+            .any(|interaction| *interaction != Interaction::None)
+        {
+            info!("ignore mousepress because of ui interaction")
+        } else {
+            let position = mouse_position.world_position;
+            editor_meta.current_selection_start = Some(position);
+            editor_meta.current_stroke.clear();
+        }
+    }
+    let stroking = editor_meta.tool.is_stroke() && editor_meta.current_selection_start.is_some();
+    if let Some(tile) = hovered_tile.filter(|_| stroking) {
+        // fill the gaps if the cursor moved more than one tile since the last frame
+        let stroke = match editor_meta.current_stroke.last() {
+            Some(last) if *last == tile => Vec::new(),
+            Some(last) => iter_grid_line((*last).into(), tile.into())
+                .into_iter()
+                .skip(1)
+                .map(UVec2::from)
+                .collect(),
+            None => vec![tile],
+        };
+        editor_meta.current_stroke.extend(stroke);
+    }
+    if mouse.just_released(MouseButton::Left) {
+        let Some(start_pos) = editor_meta.current_selection_start else {
+            return;
+        };
+        if ui_q
+            .iter()
+            .any(|interaction| *interaction != Interaction::None)
+        {
+            info!("ignore mouserelease because of ui interaction");
+        } else {
+            let current_pos = mouse_position.world_position;
+            match editor_meta.tool {
+                EditorTool::Rectangle => {
+                    event_writer.write(EditorEvents::SpawnTiles(start_pos, current_pos));
+                }
+                EditorTool::Select => {
+                    let start = UVec2::from(convert_to_tile_pos(start_pos));
+                    let end = UVec2::from(convert_to_tile_pos(current_pos));
+                    editor_meta.selection = Some(URect::from_corners(start, end));
+                }
+                EditorTool::Line => {
+                    let positions = iter_grid_line(
+                        convert_to_tile_pos(start_pos),
+                        convert_to_tile_pos(current_pos),
+                    );
+                    event_writer.write(EditorEvents::PaintTiles(
+                        positions.into_iter().map(UVec2::from).collect(),
+                    ));
+                }
+                EditorTool::FloodFill => {
+                    if let Some(tile) = hovered_tile {
+                        let positions = editor_meta.flood_fill(tile, &level_tiles);
+                        event_writer.write(EditorEvents::PaintTiles(positions));
+                    }
+                }
+                EditorTool::Freehand => {
+                    let stroke = std::mem::take(&mut editor_meta.current_stroke);
+                    event_writer.write(EditorEvents::PaintTiles(stroke));
+                }
+                EditorTool::Erase => {
+                    let stroke = std::mem::take(&mut editor_meta.current_stroke);
+                    event_writer.write(EditorEvents::EraseTiles(stroke));
+                }
+            }
+        }
+        editor_meta.current_selection_start = None;
+        editor_meta.current_stroke.clear();
+    }
+}
+/// `Ctrl+S` saves the level in place, `Ctrl+Shift+S` asks for a new file
+fn check_save_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut event_writer: EventWriter<EditorEvents>,
+) {
+    if !keys.just_pressed(KeyCode::KeyS)
+        || !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
+        return;
+    }
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        event_writer.write(EditorEvents::SaveLevelAs);
+    } else {
+        event_writer.write(EditorEvents::SaveLevel);
+    }
+}
+impl EditorMeta {
+    /// Positions a [`EditorTool::FloodFill`] at `start` would paint.
+    /// Empty if the region already has the selected tile
+    fn flood_fill(&self, start: UVec2, level_tiles: &LevelTiles) -> Vec<UVec2> {
+        let tiles = level_tiles.layer_indices(self.layer_type);
+        let selected = self.selected_tile.as_ref().map(|atlas| atlas.index);
+        if tiles.get(&start).copied() == selected {
+            return Vec::new();
+        }
+        tools::flood_fill(start, level_tiles.map_size.0, &tiles)
+    }
+    /// The selected autotile brush
+    fn autotile_group<'a>(&self, textures: &'a map::Textures) -> Option<&'a AutotileGroup> {
+        let groups = &textures.pack[&self.layer_type].autotile;
+        self.autotile.and_then(|i| groups.get(i))
+    }
+    /// Edited prefabs no longer match their file, so they stop following it.
+    /// Returns the instances that were linked before, so undo can link them again
+    fn unlink_prefabs(
+        &mut self,
+        positions: impl IntoIterator<Item = UVec2>,
+    ) -> Vec<io::PrefabInstance> {
+        let mut unlinked = Vec::new();
+        for pos in positions {
+            for instance in &mut self.prefab_instances {
+                if instance.linked && instance.area().contains(pos) {
+                    instance.linked = false;
+                    unlinked.push(instance.clone());
+                }
+            }
+        }
+        unlinked
+    }
+    /// Restores the [`linked`](io::PrefabInstance::linked) flag of instances recorded in [`Change::unlinked`]
+    fn set_linked(&mut self, instances: &[io::PrefabInstance], linked: bool) {
+        for instance in &mut self.prefab_instances {
+            if instances
+                .iter()
+                .any(|other| other.path == instance.path && other.pos == instance.pos)
+            {
+                instance.linked = linked;
+            }
+        }
+    }
+}
+#[derive(Component)]
+struct PlayerDeadsUiRoot;
+fn draw_deads(
+    trigger: Trigger<UiRespawnTrigger>,
+    mut commands: Commands,
+    editor_meta: Res<EditorMeta>,
+    mut texts: Query<&mut Text>,
+    ui_root: Option<Single<&Children, With<PlayerDeadsUiRoot>>>,
+) {
+    if !matches!(trigger.event(), UiRespawnTrigger::Deads) {
+        return;
+    }
+    if let Some(ui_root) = &ui_root {
+        for child in ui_root.as_ref() {
+            let mut text = texts.get_mut(*child).unwrap();
+            text.0 = editor_meta.deads.to_string();
+        }
+    } else {
+        commands.spawn((
+            Node {
+                left: Val::Percent(5.),
+                // right: Val::Percent(10.),
+                width: Val::Percent(10.),
+                height: Val::Percent(10.),
+                top: Val::Percent(5.),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            PlayerDeadsUiRoot,
+            children![widget::player_dead(editor_meta.deads),],
+        ));
+    }
+}
+fn draw_selection_indicator(
+    mut my_gizmos: Gizmos<DefaultGizmoConfigGroup>,
+    mouse_position: Res<MousePosition>,
+    editor_meta: Res<EditorMeta>,
+    map_size: Res<MapSize>,
+    level_tiles: LevelTiles,
+) {
+    let hovered_tile = mouse_position.to_tilepos(&map_size).map(UVec2::from);
+    let draw_cell = |gizmos: &mut Gizmos, cell: UVec2, color: Srgba| {
+        let center = tile_to_world(&cell.into(), map_size.0, TILEMAP_OFFSET.extend(0.)).xy();
+        gizmos.rect_2d(
+            Isometry2d::new(center, Rot2::IDENTITY),
+            Vec2::splat(TILESIZE as f32 - 2.),
+            color,
+        );
+    };
+    if editor_meta.pasting {
+        let (Some(stamp), Some(tile)) = (&editor_meta.clipboard, hovered_tile) else {
+            return;
+        };
+        for cell in stamp.footprint(tile) {
+            draw_cell(&mut my_gizmos, cell, palettes::tailwind::YELLOW_200);
+        }
+        for (_, tiles) in stamp.place(tile) {
+            for tile in tiles {
+                draw_cell(&mut my_gizmos, tile.pos, palettes::tailwind::BLUE_100);
+            }
+        }
+        return;
+    }
+    if let (EditorTool::Select, Some(selection)) = (editor_meta.tool, editor_meta.selection) {
+        let corners = [selection.min, selection.max].map(|corner| {
+            tile_to_world(&corner.into(), map_size.0, TILEMAP_OFFSET.extend(0.)).xy()
+        });
+        my_gizmos.rect_2d(
+            Isometry2d::new((corners[0] + corners[1]) / 2., Rot2::IDENTITY),
+            (corners[1] - corners[0]).abs() + TILESIZE as f32,
+            palettes::tailwind::YELLOW_200,
+        );
+    }
+    let start_pos = editor_meta.current_selection_start;
+    let (cells, color) = match editor_meta.tool {
+        EditorTool::Rectangle | EditorTool::Select => {
+            let Some(start_pos) = start_pos else {
+                return;
+            };
+            let cur_pos = mouse_position
+                .to_tilepos_vec2(&map_size)
+                .unwrap_or(start_pos);
+            let center = (cur_pos + start_pos) / 2.;
+            let size = cur_pos - start_pos;
+            my_gizmos.rect_2d(
+                Isometry2d::new(center, Rot2::IDENTITY),
+                size,
+                palettes::tailwind::BLUE_100,
+            );
+            return;
+        }
+        EditorTool::Line => {
+            let (Some(start_pos), Some(tile)) = (start_pos, hovered_tile) else {
+                return;
+            };
+            let line = iter_grid_line(convert_to_tile_pos(start_pos), tile.into());
+            let cells = line.into_iter().map(UVec2::from).collect();
+            (cells, palettes::tailwind::BLUE_100)
+        }
+        EditorTool::FloodFill => {
+            let Some(tile) = hovered_tile else {
+                return;
+            };
+            (
+                editor_meta.flood_fill(tile, &level_tiles),
+                palettes::tailwind::BLUE_100,
+            )
+        }
+        EditorTool::Freehand => (
+            editor_meta.current_stroke.clone(),
+            palettes::tailwind::BLUE_100,
+        ),
+        EditorTool::Erase => (
+            editor_meta.current_stroke.clone(),
+            palettes::tailwind::RED_400,
+        ),
+    };
+    for cell in cells {
+        draw_cell(&mut my_gizmos, cell, color);
+    }
+}
+fn current_tile_ui(
+    mut my_gizmos: Gizmos<DefaultGizmoConfigGroup>,
+    mouse_position: Res<MousePosition>,
+    map_size: Res<MapSize>,
+) {
+    let position = mouse_position.to_tilepos_vec2(&map_size);
+    if let Some(position) = position {
+        my_gizmos.rect_2d(
+            Isometry2d::new(position, Rot2::IDENTITY),
+            Vec2::new(TILESIZE as f32, TILESIZE as f32),
+            palettes::basic::FUCHSIA,
+        );
+    }
+}
+#[derive(Event)]
+pub enum EditorEvents {
+    SpawnTiles(Vec2, Vec2),
+    RespawnPlayer,
+    /// Writes the level back to the file it was loaded from, see [`EditorMeta::level_path`]
+    SaveLevel,
+    /// Asks for the file to save the level to with a file dialog.
+    /// The web has no save dialog, there it asks for the name of the level instead
+    SaveLevelAs,
+    /// Name of the file without the extension so 'assets/level/level1.ron' becomes 'level1'.
+    /// Other formats like Tiled maps keep their extension, see [`io::level_path`]
+    /// If [`Option::None`] is provided then a file dialog is opened.
+    /// Selecting a campaign in the dialog starts its first level
+    LoadLevel {
+        name: Option<String>,
+    },
+    ToggleEditMode,
+    /// Changes the width and height of the current map.
+    /// Tiles that are outside of the new size are removed
+    ResizeMap(UVec2),
+    /// Places the selected tile at every position
+    PaintTiles(Vec<UVec2>),
+    /// Removes the tiles at every position of the current layer
+    EraseTiles(Vec<UVec2>),
+    /// Places the copied [`tools::Stamp`] with its bottom left corner at the position
+    PasteStamp(UVec2),
+    /// Reverts the last tile edit, see [`history::EditHistory`]
+    Undo,
+    Redo,
+}
+pub fn spawn_tiled(
+    tilemap_entity: Entity,
+    position: TilePos,
+    index: usize,
+    layer_type: LayerType,
+) -> impl Bundle {
+    (
+        TileBundle {
+            position,
+            tilemap_id: TilemapId(tilemap_entity),
+            texture_index: TileTextureIndex(index as u32),
+            ..default()
+        },
+        layer_type,
+    )
+}
+/// Overrides the index, position and properties of the tile when storing level to disk
+#[derive(Component)]
+pub struct SaveOverride(pub io::Tile);
+#[derive(Component)]
+pub struct RemoveOnLevelSwap;
+/// All tiles of the current level
+#[derive(SystemParam)]
+pub struct LevelTiles<'w, 's> {
+    tiles_q: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static TileTextureIndex,
+            &'static TilePos,
+            &'static LayerType,
+            Option<&'static SaveOverride>,
+            Option<&'static TileProperties>,
+        ),
+    >,
+    override_tiles: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static LayerType,
+            &'static SaveOverride,
+            Option<&'static ChildOf>,
+        ),
+    >,
+    map_size: Res<'w, MapSize>,
+}
+impl LevelTiles<'_, '_> {
+    /// Atlas index of every tile of `layer_type` by position
+    pub fn layer_indices(&self, layer_type: LayerType) -> HashMap<UVec2, usize> {
+        let tiles = self
+            .tiles_q
+            .iter()
+            .filter(|(_, _, _, tile_layer_type, save_override, _)| {
+                **tile_layer_type == layer_type && save_override.is_none()
+            })
+            .map(|(_, index, pos, ..)| (pos.into(), index.0 as usize));
+        let override_tiles = self
+            .override_tiles
+            .iter()
+            .filter(|(_, tile_layer_type, ..)| **tile_layer_type == layer_type)
+            .map(|(_, _, tile, _)| (tile.0.pos, tile.0.index));
+        tiles.chain(override_tiles).collect()
+    }
+    /// Collects the tiles in the format they are stored on disk
+    pub fn to_save_file(&self) -> SaveFile {
+        let mut level = io::SaveFile {
+            size: self.map_size.0,
+            ..default()
+        };
+        for (_e, texture_index, tile_pos, tile_layer_type, save_override, properties) in
+            &self.tiles_q
+        {
+            // will processed later
+            if save_override.is_some() {
+                continue;
+            }
+            let layer = level.layers.entry(*tile_layer_type).or_insert(io::Layer {
+                tiles: Vec::default(),
+            });
+            let index = texture_index.0 as usize;
+            layer.tiles.push(io::Tile {
+                pos: tile_pos.into(),
+                index,
+                properties: properties.map(|p| p.0.clone()).unwrap_or_default(),
+            })
+        }
+        for (_e, tile_layer_type, tile, _) in &self.override_tiles {
+            let layer = level.layers.entry(*tile_layer_type).or_insert(io::Layer {
+                tiles: Vec::default(),
+            });
+            layer.tiles.push(tile.0.clone());
+        }
+        level
+    }
+}
+/// Replaces the tiles of the current layer at `positions` with `index`.
+/// [`None`] only removes the tiles.
+/// With an autotile brush the painted tiles and their neighbours of the group get the variant that fits
+fn paint_tiles(
+    commands: &mut Commands,
+    level_tiles: &LevelTiles,
+    tile_map: &mut Query<(
+        Entity,
+        &mut TileStorage,
+        &TilemapSize,
+        &LayerType,
+        &LayerControls,
+    )>,
+    textures: &map::Textures,
+    editor_meta: &EditorMeta,
+    positions: &[UVec2],
+    index: Option<usize>,
+) -> TileEdit {
+    let layer_type = editor_meta.layer_type;
+    let mut positions: HashSet<UVec2> = positions.iter().copied().collect();
+    let group = editor_meta.autotile_group(textures);
+    let layer = match group {
+        Some(_) => level_tiles
+            .to_save_file()
+            .layers
+            .remove(&layer_type)
+            .map(|layer| layer.tiles)
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let tiles = painted_tiles(&layer, group, &positions, index, level_tiles.map_size.0);
+    positions.extend(tiles.keys());
+    let tiles: Vec<Tile> = tiles.into_values().collect();
+    let before = replace_tiles(
+        commands,
+        level_tiles,
+        tile_map,
+        &textures.pack[&layer_type].rules,
+        layer_type,
+        |pos| positions.contains(&pos),
+        &tiles,
+    );
+    TileEdit {
+        layer_type,
+        before,
+        after: tiles,
+    }
+}
+/// Tiles placed by painting `index` at `positions`, or by erasing them if it is [`None`].
+/// With an autotile `group` this includes the neighbours in `layer` that change their variant,
+/// they keep their properties
+fn painted_tiles(
+    layer: &[Tile],
+    group: Option<&AutotileGroup>,
+    positions: &HashSet<UVec2>,
+    index: Option<usize>,
+    map_size: UVec2,
+) -> HashMap<UVec2, Tile> {
+    let mut tiles: HashMap<UVec2, Tile> = match index {
+        Some(index) => positions
+            .iter()
+            .map(|pos| {
+                let tile = Tile {
+                    pos: *pos,
+                    index,
+                    ..default()
+                };
+                (*pos, tile)
+            })
+            .collect(),
+        None => HashMap::new(),
+    };
+    let Some(group) = group else {
+        return tiles;
+    };
+    let existing: HashMap<UVec2, &Tile> = layer.iter().map(|tile| (tile.pos, tile)).collect();
+    let mut indices: HashMap<UVec2, usize> = existing
+        .iter()
+        .map(|(pos, tile)| (*pos, tile.index))
+        .collect();
+    for pos in positions {
+        match index {
+            Some(index) => indices.insert(*pos, index),
+            None => indices.remove(pos),
+        };
+    }
+    for (pos, index) in group.resolve(&indices, positions, map_size) {
+        let properties = match existing.get(&pos) {
+            Some(tile) if !positions.contains(&pos) => tile.properties.clone(),
+            _ => default(),
+        };
+        let tile = Tile {
+            pos,
+            index,
+            properties,
+        };
+        tiles.insert(pos, tile);
+    }
+    tiles
+}
+/// Despawns the tiles of `layer_type` at the positions where `remove` is true and spawns `tiles`.
+/// Placing a player also removes the old one, because only one player can exist.
+/// Returns the removed tiles in the format they are stored on disk
+fn replace_tiles(
+    commands: &mut Commands,
+    level_tiles: &LevelTiles,
+    tile_map: &mut Query<(
+        Entity,
+        &mut TileStorage,
+        &TilemapSize,
+        &LayerType,
+        &LayerControls,
+    )>,
+    rules: &[Rule],
+    layer_type: LayerType,
+    remove: impl Fn(UVec2) -> bool,
+    tiles: &[Tile],
+) -> Vec<Tile> {
+    let is_player = |index: usize| {
+        rules.iter().any(|rule| {
+            rule.target_index == index
+                && matches!(
+                    rule.on_spawn,
+                    OnSpawnTrigger::Player | OnSpawnTrigger::PlayerSpawnPlatform
+                )
+        })
+    };
+    let places_player = tiles.iter().any(|tile| is_player(tile.index));
+    let remove = |pos: UVec2, index: usize| remove(pos) || (places_player && is_player(index));
+    let mut removed = Vec::new();
+    for (e, texture_index, tile_pos, tile_layer_type, save_override, properties) in
+        &level_tiles.tiles_q
+    {
+        if layer_type != *tile_layer_type || !remove(tile_pos.into(), texture_index.0 as usize) {
+            continue;
+        }
+        // tiles with an override are recorded below
+        if save_override.is_none() {
+            removed.push(Tile {
+                pos: tile_pos.into(),
+                index: texture_index.0 as usize,
+                properties: properties.map(|p| p.0.clone()).unwrap_or_default(),
+            });
+        }
+        commands.entity(e).despawn();
+    }
+    for (e, tile_layer_type, override_tile, parent) in &level_tiles.override_tiles {
+        if layer_type != *tile_layer_type || !remove(override_tile.0.pos, override_tile.0.index) {
+            continue;
+        }
+        if let Some(parent) = parent {
+            commands.entity(parent.0).despawn();
+        } else {
+            commands.entity(e).despawn();
+        }
+        removed.push(override_tile.0.clone());
+    }
+    let (tilemap_e, mut storage, tilemap_size, ..) = tile_map
+        .iter_mut()
+        .find(|(_e, _storage, _, tile_layer_type, _)| **tile_layer_type == layer_type)
+        .unwrap();
+    for tile in tiles {
+        if tile
+            .pos
+            .cmpge(UVec2::new(tilemap_size.x, tilemap_size.y))
+            .any()
+        {
+            continue;
+        }
+        map::spawn_tile(rules, commands, tile, tilemap_e, &mut storage, layer_type);
+    }
+    removed
+}
+fn process_editor_events(
+    mut commands: Commands,
+    mut events: EventReader<EditorEvents>,
+    mut editor_meta: ResMut<EditorMeta>,
+    asset_server: Res<AssetServer>,
+    mut asset_event_writer: EventWriter<AssetEvent<SaveFile>>,
+    mut save_files: ResMut<Assets<SaveFile>>,
+    textures: Res<map::Textures>,
+    cam: Single<Entity, With<MainCamera>>,
+    level_tiles: LevelTiles,
+    mut players: Query<(&mut Player, &mut PlayerAnimation, &ChildOf, &SaveOverride)>,
+    mut enemies: Query<(&mut Visibility, &mut EnemyAnimation), With<Enemy>>,
+    mut parent_player: Query<(&mut LinearVelocity, &mut Transform)>,
+    mut tower_timer: ResMut<TowerCountdown>,
+    mut tile_map: Query<(
+        Entity,
+        &mut TileStorage,
+        &TilemapSize,
+        &LayerType,
+        &LayerControls,
+    )>,
+    mut progress: ResMut<CampaignProgress>,
+    mut history: ResMut<EditHistory>,
+) {
+    for event in events.read() {
+        // locked layers are only changed by undo and redo
+        let edited_layers: Vec<LayerType> = match event {
+            EditorEvents::SpawnTiles(..)
+            | EditorEvents::PaintTiles(_)
+            | EditorEvents::EraseTiles(_) => vec![editor_meta.layer_type],
+            EditorEvents::PasteStamp(origin) => editor_meta
+                .clipboard
+                .iter()
+                .flat_map(|stamp| stamp.place(*origin))
+                .map(|(layer_type, _)| layer_type)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let locked = tile_map.iter().find(|(.., layer_type, controls)| {
+            controls.locked && edited_layers.contains(layer_type)
+        });
+        if let Some((.., layer_type, _)) = locked {
+            commands.trigger(Notification::error(format!(
+                "{} is locked",
+                layer_type.name()
+            )));
+            continue;
+        }
+        match event {
+            EditorEvents::SpawnTiles(start, end) => {
+                let start_pos = convert_to_tile_pos(*start);
+                let end_pos = convert_to_tile_pos(*end);
+                let v = iter_grid_rect(start_pos, end_pos);
+                info!("spawning {} tiles", v.len());
+                let positions: Vec<UVec2> = v.into_iter().map(UVec2::from).collect();
+                let index = editor_meta.selected_tile.as_ref().map(|atlas| atlas.index);
+                let edit = paint_tiles(
+                    &mut commands,
+                    &level_tiles,
+                    &mut tile_map,
+                    &textures,
+                    &editor_meta,
+                    &positions,
+                    index,
+                );
+                if !edit.before.is_empty() {
+                    info!("despawned {} tiles", edit.before.len());
+                }
+                let unlinked = editor_meta.unlink_prefabs(edit.positions());
+                history.push(edit, unlinked);
+            }
+            EditorEvents::PaintTiles(positions) | EditorEvents::EraseTiles(positions) => {
+                let index = match event {
+                    EditorEvents::PaintTiles(_) => {
+                        editor_meta.selected_tile.as_ref().map(|atlas| atlas.index)
+                    }
+                    _ => None,
+                };
+                let edit = paint_tiles(
+                    &mut commands,
+                    &level_tiles,
+                    &mut tile_map,
+                    &textures,
+                    &editor_meta,
+                    positions,
+                    index,
+                );
+                let unlinked = editor_meta.unlink_prefabs(edit.positions());
+                history.push(edit, unlinked);
+            }
+            EditorEvents::Undo | EditorEvents::Redo => {
+                let undo = matches!(event, EditorEvents::Undo);
+                let change = if undo { history.undo() } else { history.redo() };
+                let Some(change) = change else {
+                    continue;
+                };
+                for edit in &change.edits {
+                    let positions: HashSet<UVec2> = edit.positions().collect();
+                    let tiles = if undo { &edit.before } else { &edit.after };
+                    replace_tiles(
+                        &mut commands,
+                        &level_tiles,
+                        &mut tile_map,
+                        &textures.pack[&edit.layer_type].rules,
+                        edit.layer_type,
+                        |pos| positions.contains(&pos),
+                        tiles,
+                    );
+                }
+                editor_meta.set_linked(&change.unlinked, undo);
+                if let Some(prefab) = &change.prefab {
+                    if undo {
+                        // the instance may have been unlinked since
+                        editor_meta
+                            .prefab_instances
+                            .retain(|p| p.path != prefab.path || p.pos != prefab.pos);
+                    } else {
+                        editor_meta.prefab_instances.push(prefab.clone());
+                    }
+                }
+            }
+            EditorEvents::PasteStamp(origin) => {
+                let Some(stamp) = &editor_meta.clipboard else {
+                    continue;
+                };
+                let footprint: HashSet<UVec2> = stamp.footprint(*origin).collect();
+                let mut edits = Vec::new();
+                for (layer_type, tiles) in stamp.place(*origin) {
+                    let before = replace_tiles(
+                        &mut commands,
+                        &level_tiles,
+                        &mut tile_map,
+                        &textures.pack[&layer_type].rules,
+                        layer_type,
+                        |pos| footprint.contains(&pos),
+                        &tiles,
+                    );
+                    edits.push(TileEdit {
+                        layer_type,
+                        before,
+                        after: tiles,
+                    });
+                }
+                let prefab = stamp.prefab().map(|path| io::PrefabInstance {
+                    path: path.into(),
+                    pos: *origin,
+                    size: stamp.size(),
+                    linked: !editor_meta.place_unlinked,
+                });
+                let mut unlinked = Vec::new();
+                for edit in &edits {
+                    unlinked.extend(editor_meta.unlink_prefabs(edit.positions()));
+                }
+                if let Some(prefab) = &prefab {
+                    editor_meta.prefab_instances.push(prefab.clone());
+                }
+                history.push_change(Change {
+                    edits,
+                    prefab,
+                    unlinked,
+                });
+            }
+            EditorEvents::SaveLevel | EditorEvents::SaveLevelAs => {
+                let mut level = level_tiles.to_save_file();
+                level.meta = editor_meta.level_meta.clone();
+                level.prefabs = editor_meta.prefab_instances.clone();
+                let path = match (event, &editor_meta.level_path) {
+                    (EditorEvents::SaveLevel, Some(path)) => Some(path.clone()),
+                    _ if cfg!(target_arch = "wasm32") => {
+                        commands.trigger(UiRespawnTrigger::SavePrompt);
+                        continue;
+                    }
+                    _ => {
+                        let file_name = editor_meta
+                            .level_path
+                            .as_ref()
+                            .and_then(|path| path.file_name()?.to_str())
+                            .unwrap_or("level.ron");
+                        io::select_save_file(file_name)
+                    }
+                };
+                let Some(path) = path else {
+                    info!("Save was aborted since no file was selected");
+                    continue;
+                };
+                match io::write_save_file(&path, &level) {
+                    Ok(()) => {
+                        commands.trigger(Notification::info(format!("Saved {}", path.display())));
+                        editor_meta.current_level_name = io::level_name(&path);
+                        editor_meta.level_path = Some(path);
+                    }
+                    Err(err) => commands.trigger(Notification::error(format!(
+                        "Could not save {}: {err}",
+                        path.display()
+                    ))),
+                }
+            }
+            EditorEvents::LoadLevel { name } => {
+                let path = match name {
+                    Some(name) => Some(io::level_path(name)),
+                    None => io::select_file(),
+                };
+                let Some(path) = path else {
+                    info!("loading failed");
+                    continue;
+                };
+                if path.to_string_lossy().ends_with(".campaign.ron") {
+                    info!("start loading campaign {path:?}");
+                    progress.campaign = asset_server.load(path);
+                    progress.start_pending = true;
+                    continue;
+                }
+                info!("start loading new level {path:?}");
+                // loading from a string also parses labels like 'world.ldtk#Level_1'
+                let asset_path = path.to_string_lossy().into_owned();
+                let handle = asset_server.load::<SaveFile>(&asset_path);
+                // the level browser keeps levels loaded, reading them again picks up saved changes
+                // and fires the asset event [map::load_level()] waits for
+                if asset_server.is_loaded_with_dependencies(handle.id()) {
+                    asset_server.reload(asset_path);
+                }
+                editor_meta.current_level = handle;
+                editor_meta.current_level_name = io::level_name(&path);
+                // imported levels are saved as a new file
+                editor_meta.level_path = Some(path)
+                    .filter(|path| path.extension().is_some_and(|extension| extension == "ron"));
+                history.clear();
+            }
+            EditorEvents::ToggleEditMode => {
+                commands.trigger(UiRespawnTrigger::OverviewRespawn);
+                editor_meta.edit_mode = !editor_meta.edit_mode;
+                if editor_meta.edit_mode {
+                    commands.trigger(UiRespawnTrigger::TileSelectionRespawn);
+                } else {
+                    commands.trigger(UiRespawnTrigger::TileSelectionRemove);
+                }
+            }
+            EditorEvents::ResizeMap(size) => {
+                let mut level = level_tiles.to_save_file();
+                level.size = size.max(UVec2::ONE);
+                level.meta = editor_meta.level_meta.clone();
+                level.prefabs = editor_meta.prefab_instances.clone();
+                for layer in level.layers.values_mut() {
+                    layer.tiles.retain(|tile| tile.pos.cmplt(level.size).all());
+                }
+                info!("resizing map to {}", level.size);
+                // the resized level only exists in memory until it is saved
+                let handle = save_files.add(level);
+                asset_event_writer.write(AssetEvent::Modified { id: handle.id() });
+                editor_meta.current_level = handle;
+                history.clear();
+            }
+            EditorEvents::RespawnPlayer => {
+                for (mut player, mut animation, parent, tile) in &mut players {
+                    let Ok((mut velo, mut transform)) = parent_player.get_mut(parent.0) else {
+                        continue;
+                    };
+                    tower_timer.timer = Some(Timer::from_seconds(0., TimerMode::Once));
+                    *animation = PlayerAnimation::Idle;
+                    player.mode = PlayerMode::Normal;
+                    let translation = tile_to_world(
+                        &tile.0.pos.into(),
+                        level_tiles.map_size.0,
+                        TILEMAP_OFFSET.extend(LayerType::Entities.z() + 1.),
+                    );
+                    // this is just a hack because the respawn event sometimes fires 2 times
+                    if translation.distance(transform.translation) > 1. {
+                        editor_meta.deads += 1;
+                        editor_meta.level_deads += 1;
+                        commands.trigger(UiRespawnTrigger::Deads);
+                    }
+                    commands
+                        .entity(*cam)
+                        .insert(ScreenShake::new(TRAUMA, 50., 1.));
+                    transform.translation = translation;
+                    **velo = Vec2::ZERO;
+                }
+                for (mut visibility, mut mode) in &mut enemies {
+                    *visibility = Visibility::Inherited;
+                    *mode = EnemyAnimation::Spawn;
+                }
+            }
+        }
+    }
+}
+fn update_control_images(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut control_images: Query<(Entity, &mut ControlImage)>,
+) {
+    let delta = time.delta();
+    for (e, mut image) in &mut control_images {
+        image.0.tick(delta);
+        if image.0.finished() {
+            commands.entity(e).despawn();
+        }
+    }
+}
+#[derive(Component)]
+struct ControlImage(Timer);
+fn show_control_image(
+    trigger: Trigger<UiRespawnTrigger>,
+    mut commands: Commands,
+    textures: Res<map::Textures>,
+    mut control_images: Query<&mut ControlImage>,
+) {
+    if !matches!(trigger.event(), UiRespawnTrigger::ShowControlImage) {
+        return;
+    }
+    let timer = Timer::from_seconds(5., TimerMode::Once);
+    if !control_images.is_empty() {
+        for mut control_image in &mut control_images {
+            control_image.0 = timer.clone();
+        }
+        return;
+    }
+    commands.spawn((
+        Node {
+            left: Val::Percent(70.),
+            width: Val::Percent(30.),
+            height: Val::Percent(10.),
+            align_items: AlignItems::Center,
+            top: Val::Percent(80.),
+            ..default()
+        },
+        ControlImage(timer),
+        // BackgroundColor(BLUE_500.into()),
+        children![widget::tile_image(ImageNode::new(textures.keys.clone())),],
+    ));
+}
+#[derive(Component)]
+struct LevelTitle(Timer);
+/// Shows name, author and par of the level for a few seconds after it was loaded
+/// and a short summary once the last level of the campaign was finished
+fn show_level_title(
+    trigger: Trigger<UiRespawnTrigger>,
+    mut commands: Commands,
+    editor_meta: Res<EditorMeta>,
+    progress: Res<CampaignProgress>,
+    campaigns: Res<Assets<Campaign>>,
+    titles: Query<Entity, With<LevelTitle>>,
+) {
+    let event = trigger.event();
+    if !matches!(
+        event,
+        UiRespawnTrigger::LevelTitle | UiRespawnTrigger::CampaignFinished
+    ) {
+        return;
+    }
+    for e in &titles {
+        commands.entity(e).despawn();
+    }
+    let meta = &editor_meta.level_meta;
+    let campaign = campaigns.get(progress.campaign.id()).filter(|campaign| {
+        editor_meta
+            .current_level_name
+            .as_ref()
+            .is_some_and(|name| campaign.contains(name))
+    });
+    let (title, subtitle) = if let UiRespawnTrigger::CampaignFinished = event {
+        let title = match campaign {
+            Some(campaign) => format!("{} complete", campaign.name),
+            None => format!("{} complete", meta.name),
+        };
+        (title, vec![format!("{} deaths", editor_meta.deads)])
+    } else {
+        if meta.name.is_empty() {
+            return;
+        }
+        let mut subtitle = Vec::new();
+        let chapter = campaign
+            .and_then(|campaign| campaign.chapter_of(editor_meta.current_level_name.as_deref()?));
+        if let Some(chapter) = chapter {
+            subtitle.push(chapter.name.clone());
+        }
+        if !meta.author.is_empty() {
+            subtitle.push(format!("by {}", meta.author));
+        }
+        if let Some(par_time) = meta.par_time {
+            subtitle.push(format!("par {par_time:.0}s"));
+        }
+        if let Some(par_deaths) = meta.par_deaths {
+            subtitle.push(format!("par {par_deaths} deaths"));
+        }
+        (meta.name.clone(), subtitle)
+    };
+    commands.spawn((
+        widget::ui_root("Level title"),
+        LevelTitle(Timer::from_seconds(3., TimerMode::Once)),
+        children![widget::header(title), widget::label(subtitle.join("  -  ")),],
+    ));
+}
+fn update_level_title(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut titles: Query<(Entity, &mut LevelTitle)>,
+) {
+    for (e, mut title) in &mut titles {
+        title.0.tick(time.delta());
+        if title.0.finished() {
+            commands.entity(e).despawn();
+        }
+    }
+}
+#[derive(Component)]
+struct OverviewUiRoot;
+fn init_ui_overview(
+    trigger: Trigger<UiRespawnTrigger>,
+    q: Query<Entity, With<OverviewUiRoot>>,
+    mut commands: Commands,
+    editor_meta: Res<EditorMeta>,
+    playtest: Res<Playtest>,
+) {
+    // cleanup in case of redrawing
+    let UiRespawnTrigger::OverviewRespawn = trigger.event() else {
+        return;
+    };
+    for e in &q {
+        commands.entity(e).despawn();
+    }
+    let node = commands
+        .spawn((
+            Node {
+                left: Val::Percent(20.),
+                width: Val::Percent(60.),
+                height: Val::Percent(4.),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(DEAD_BACKGROUND),
+            OverviewUiRoot,
+            children![
+                widget::overview_button(
+                    OverviewButton::EditMode,
+                    if editor_meta.edit_mode {
+                        "Edit mode"
+                    } else {
+                        "Play mode"
+                    }
+                ),
+                widget::overview_button(OverviewButton::Load, "Load"),
+            ],
+        ))
+        .id();
+    // the playtest level has the player at another position
+    if !playtest.is_active() {
+        commands
+            .entity(node)
+            .with_child(widget::overview_button(OverviewButton::Save, "Save"))
+            .with_child(widget::overview_button(OverviewButton::SaveAs, "Save as"));
+    }
+
+    if editor_meta.edit_mode {
+        commands.entity(node).with_child(widget::overview_button(
+            OverviewButton::LayerType,
+            editor_meta.layer_type.name(),
+        ));
+        commands.entity(node).with_child(widget::overview_button(
+            OverviewButton::Tool,
+            editor_meta.tool.name(),
+        ));
+        commands
+            .entity(node)
+            .with_child(widget::overview_button(OverviewButton::LevelMeta, "Level"));
+    }
+}
+
+#[derive(Event)]
+pub enum UiRespawnTrigger {
+    TileSelectionRespawn,
+    TileSelectionRemove,
+    OverviewRespawn,
+    ShowControlImage,
+    Deads,
+    LevelTitle,
+    LevelMetaToggle,
+    CampaignFinished,
+    PrefabPanelRespawn,
+    LevelBrowserToggle,
+    LevelBrowserRespawn,
+    LayerPanelRespawn,
+    SavePrompt,
+}
+#[derive(Component)]
+struct TileSelectionUiRoot;
+fn ui_tile_selection_update(
+    trigger: Trigger<UiRespawnTrigger>,
+    editor_meta: Res<EditorMeta>,
+    mut commands: Commands,
+    q: Query<Entity, With<TileSelectionUiRoot>>,
+    textures: Res<map::Textures>,
+    texture_atlas_layouts: Res<Assets<TextureAtlasLayout>>,
+) {
+    let event = trigger.event();
+    if !matches!(
+        event,
+        UiRespawnTrigger::TileSelectionRespawn | UiRespawnTrigger::TileSelectionRemove
+    ) {
+        return;
+    }
+    // cleanup in case of redrawing
+    for e in &q {
+        commands.entity(e).despawn();
+    }
+    if let UiRespawnTrigger::TileSelectionRemove = event {
+        return;
+    }
+    commands
+        .spawn((
+            widget::tile_selection_root(Val::Percent(88.)),
+            TileSelectionUiRoot,
+        ))
+        .with_children(|parent| {
+            // scrolling node
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    align_self: AlignSelf::Stretch,
+                    align_content: AlignContent::End,
+                    overflow: Overflow::scroll_y(), // n.b.
+                    height: Val::Percent(100.),
+                    width: Val::Percent(100.),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let textures = &textures.pack[&editor_meta.layer_type];
+                    let atlas = texture_atlas_layouts.get(textures.layout.id()).unwrap();
+                    if !textures.autotile.is_empty() {
+                        parent
+                            .spawn(Node {
+                                flex_wrap: FlexWrap::Wrap,
+                                column_gap: Val::Px(6.),
+                                row_gap: Val::Px(6.),
+                                margin: UiRect::all(Val::Px(4.)),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                for (i, group) in textures.autotile.iter().enumerate() {
+                                    parent.spawn(autotile_button(
+                                        i,
+                                        &group.name,
+                                        editor_meta.autotile == Some(i),
+                                    ));
+                                }
+                            });
+                    }
+                    parent.spawn((
+                        widget::tile_container(Val::Percent(11.)),
+                        children![widget::tile_image(ImageNode::solid_color(
+                            palettes::basic::WHITE.into()
+                        ))],
+                    ));
+                    for i in 0..atlas.len() / 2 {
+                        parent.spawn((
+                            widget::tile_container(Val::Percent(10.)),
+                            children![
+                                widget::tile_image(ImageNode::from_atlas_image(
+                                    textures.texture.clone(),
+                                    TextureAtlas {
+                                        layout: textures.layout.clone(),
+                                        index: i * 2,
+                                    },
+                                )),
+                                widget::tile_image(ImageNode::from_atlas_image(
+                                    textures.texture.clone(),
+                                    TextureAtlas {
+                                        layout: textures.layout.clone(),
+                                        index: i * 2 + 1,
+                                    },
+                                ))
+                            ],
+                        ));
+                    }
+                });
+        });
+}
+
+#[derive(Component)]
+pub struct TileButton;
+/// Selects the autotile group with this index as brush
+#[derive(Component)]
+struct AutotileButton(usize);
+fn autotile_button(index: usize, name: &str, selected: bool) -> impl Bundle {
+    let color = if selected {
+        PRESSED_BUTTON
+    } else {
+        NORMAL_BUTTON
+    };
+    (
+        Button,
+        AutotileButton(index),
+        Text::new(name),
+        TextFont::from_font_size(18.),
+        BackgroundColor(DEAD_BACKGROUND),
+        Outline::new(Val::Px(4.0), Val::ZERO, color),
+        Node {
+            padding: UiRect::horizontal(Val::Px(4.)),
+            ..default()
+        },
+    )
+}
+fn autotile_button_system(
+    mut buttons: Query<(&Interaction, &AutotileButton, &mut Outline)>,
+    interaction_q: Query<(), (Changed<Interaction>, With<AutotileButton>)>,
+    mut editor_meta: ResMut<EditorMeta>,
+    textures: Res<map::Textures>,
+) {
+    if interaction_q.is_empty() {
+        return;
+    }
+    for (interaction, button, _) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let pack = &textures.pack[&editor_meta.layer_type];
+        let Some(group) = pack.autotile.get(button.0) else {
+            continue;
+        };
+        editor_meta.autotile = Some(button.0);
+        editor_meta.selected_tile = Some(TextureAtlas {
+            layout: pack.layout.clone(),
+            index: group.default,
+        });
+    }
+    for (interaction, button, mut outline) in &mut buttons {
+        outline.color = if editor_meta.autotile == Some(button.0) {
+            PRESSED_BUTTON
+        } else if *interaction == Interaction::Hovered {
+            HOVERED_BUTTON
+        } else {
+            NORMAL_BUTTON
+        };
+    }
+}
+#[derive(Component)]
+pub enum OverviewButton {
+    EditMode,
+    LayerType,
+    Tool,
+    Save,
+    SaveAs,
+    Load,
+    LevelMeta,
+}
+fn overview_button_system(
+    mut commands: Commands,
+    mut layer_node_q: Query<
+        (&Interaction, &mut Text, &mut Outline, &OverviewButton),
+        (With<Button>, Changed<Interaction>),
+    >,
+    mut editor_meta: ResMut<EditorMeta>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut event_writer: EventWriter<EditorEvents>,
+    progress: Res<CampaignProgress>,
+    campaigns: Res<Assets<Campaign>>,
+) {
+    for (interaction, mut text, mut outline, overview_button) in &mut layer_node_q {
+        match *interaction {
+            Interaction::Pressed => match overview_button {
+                OverviewButton::LayerType => {
+                    editor_meta.layer_type = editor_meta.layer_type.next();
+                    editor_meta.autotile = None;
+                    **text = editor_meta.layer_type.name().into();
+                    commands.trigger(UiRespawnTrigger::TileSelectionRespawn);
+                }
+                OverviewButton::Tool => {
+                    editor_meta.tool = editor_meta.tool.next();
+                    editor_meta.pasting = false;
+                    **text = editor_meta.tool.name().into();
+                }
+                OverviewButton::Save => {
+                    event_writer.write(EditorEvents::SaveLevel);
+                }
+                OverviewButton::SaveAs => {
+                    event_writer.write(EditorEvents::SaveLevelAs);
+                }
+                OverviewButton::Load => {
+                    if keyboard_input.pressed(KeyCode::ShiftLeft) {
+                        event_writer.write(EditorEvents::LoadLevel {
+                            name: Some(progress.first_level(&campaigns)),
+                        });
+                    } else {
+                        commands.trigger(UiRespawnTrigger::LevelBrowserToggle);
+                    }
+                }
+                OverviewButton::LevelMeta => {
+                    commands.trigger(UiRespawnTrigger::LevelMetaToggle);
+                }
+                OverviewButton::EditMode => {
+                    event_writer.write(EditorEvents::ToggleEditMode);
+                    **text = if editor_meta.edit_mode {
+                        "Play mode"
+                    } else {
+                        "Edit mode"
+                    }
+                    .into();
+                }
+            },
+            Interaction::Hovered => match overview_button {
+                OverviewButton::LayerType => {
+                    text.push_str("  ");
+                    text.push_str(editor_meta.layer_type.next().name());
+                    outline.color = HOVERED_BUTTON;
+                }
+                OverviewButton::Tool => {
+                    text.push_str("  ");
+                    text.push_str(editor_meta.tool.next().name());
+                    outline.color = HOVERED_BUTTON;
+                }
+                OverviewButton::Save
+                | OverviewButton::SaveAs
+                | OverviewButton::Load
+                | OverviewButton::EditMode
+                | OverviewButton::LevelMeta => {
+                    outline.color = HOVERED_BUTTON;
+                }
+            },
+            Interaction::None => match overview_button {
+                OverviewButton::LayerType => {
+                    outline.color = NORMAL_BUTTON;
+                    **text = editor_meta.layer_type.name().into();
+                }
+                OverviewButton::Tool => {
+                    outline.color = NORMAL_BUTTON;
+                    **text = editor_meta.tool.name().into();
+                }
+                OverviewButton::Save
+                | OverviewButton::SaveAs
+                | OverviewButton::Load
+                | OverviewButton::EditMode
+                | OverviewButton::LevelMeta => {
+                    outline.color = NORMAL_BUTTON;
+                }
+            },
+        }
+    }
+}
+fn tile_button_system(
+    mut image_node_q: Query<
+        (&Interaction, &mut ImageNode, &mut Outline),
+        (With<Button>, With<TileButton>),
+    >,
+    interaction_q: Query<Entity, (Changed<Interaction>, With<Button>)>,
+    mut editor_meta: ResMut<EditorMeta>,
+) {
+    let mut new_pressed = false;
+    for entity in &interaction_q {
+        let Ok((interaction, image_node, mut outline)) = image_node_q.get_mut(entity) else {
+            continue;
+        };
+        match *interaction {
+            Interaction::Pressed => {
+                let Some(atlas) = image_node.texture_atlas.clone() else {
+                    continue;
+                };
+                editor_meta.selected_tile = Some(atlas);
+                editor_meta.autotile = None;
+                new_pressed = true;
+                outline.color = PRESSED_BUTTON;
+            }
+            Interaction::Hovered => {
+                if editor_meta.selected_tile != image_node.texture_atlas {
+                    outline.color = HOVERED_BUTTON;
+                }
+            }
+            Interaction::None => {
+                if editor_meta.selected_tile != image_node.texture_atlas {
+                    outline.color = NORMAL_BUTTON;
+                }
+            }
+        }
+    }
+    if new_pressed {
+        for (_, image_node, mut outline) in &mut image_node_q {
+            if editor_meta.selected_tile != image_node.texture_atlas
+                && outline.color == PRESSED_BUTTON
+            {
+                outline.color = NORMAL_BUTTON;
+            }
+        }
+    }
+}
+
+/// Updates the selected tile of image nodes in response to mouse input
+fn update_selected_tile(
+    mouse: Res<ButtonInput<MouseButton>>,
+    hover_map: Res<HoverMap>,
+    mut editor_meta: ResMut<EditorMeta>,
+    ui_images: Query<&ImageNode>,
+) {
+    if mouse.just_pressed(MouseButton::Left) {
+        for (_pointer, pointer_map) in hover_map.iter() {
+            for (entity, _hit) in pointer_map.iter() {
+                let Ok(layout) = ui_images.get(*entity) else {
+                    continue;
+                };
+                // the texture atlas is always of variant `Option::Some` or is the eraser
+                editor_meta.selected_tile = layout.texture_atlas.clone();
+                editor_meta.autotile = None;
+            }
+        }
+    }
+}
+/// Updates the scroll position of scrollable nodes in response to mouse input
+pub fn update_scroll_position(
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    hover_map: Res<HoverMap>,
+    mut scrolled_node_query: Query<&mut ScrollPosition>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    for mouse_wheel_event in mouse_wheel_events.read() {
+        let (mut dx, mut dy) = match mouse_wheel_event.unit {
+            MouseScrollUnit::Line => (
+                mouse_wheel_event.x * TILESIZE as f32,
+                mouse_wheel_event.y * TILESIZE as f32,
+            ),
+            MouseScrollUnit::Pixel => (mouse_wheel_event.x, mouse_wheel_event.y),
+        };
+
+        if keyboard_input.pressed(KeyCode::ControlLeft)
+            || keyboard_input.pressed(KeyCode::ControlRight)
+        {
+            std::mem::swap(&mut dx, &mut dy);
+        }
+
+        for (_pointer, pointer_map) in hover_map.iter() {
+            for (entity, _hit) in pointer_map.iter() {
+                if let Ok(mut scroll_position) = scrolled_node_query.get_mut(*entity) {
+                    scroll_position.offset_x -= dx;
+                    scroll_position.offset_y -= dy;
+                }
+            }
+        }
+    }
+}
+fn debug(
+    mut camera: Single<&mut Camera, With<MainCamera>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    players: Query<&Sprite, With<Player>>,
+    atlases: Res<Assets<TextureAtlasLayout>>,
+) {
+    if keys.just_pressed(KeyCode::KeyQ) {
+        for sprite in &players {
+            let Some(texture_atlas) = &sprite.texture_atlas else {
+                continue;
+            };
+            debug!("{texture_atlas:?}");
+            debug!("{:?}", atlases.get(texture_atlas.layout.id()));
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyT) {
+        if let ClearColorConfig::Custom(current) = camera.clear_color {
+            if current == BACKGROUND_COLOR {
+                camera.clear_color = ClearColorConfig::Custom(DEBUG_BACKGROUND_COLOR);
+            } else {
+                camera.clear_color = ClearColorConfig::Custom(BACKGROUND_COLOR);
+            }
+        }
+    }
+}
+fn check_level_loaded(
+    editor_meta: Res<EditorMeta>,
+    asset_server: Res<AssetServer>,
+    progress: Res<CampaignProgress>,
+    campaigns: Res<Assets<Campaign>>,
+    mut event_writer: EventWriter<EditorEvents>,
+) {
+    let state = asset_server.get_load_state(editor_meta.current_level.id());
+    if let Some(LoadState::Failed(failed)) = state {
+        warn!("failed loading level {failed:?}");
+        event_writer.write(EditorEvents::LoadLevel {
+            name: Some(progress.first_level(&campaigns)),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::io::TileProperty;
+
+    #[test]
+    fn autotile_keeps_properties_of_neighbours() {
+        let group: AutotileGroup = ron::from_str(
+            "(name: \"Platform\", variants: {0b0010: 11, 0b1000: 12, 0b1010: 13}, default: 10)",
+        )
+        .unwrap();
+        let properties = BTreeMap::from([("speed".to_string(), TileProperty::Int(2))]);
+        let layer = [Tile {
+            pos: UVec2::new(0, 0),
+            index: 10,
+            properties: properties.clone(),
+        }];
+        let positions = HashSet::from([UVec2::new(1, 0)]);
+        let tiles = painted_tiles(&layer, Some(&group), &positions, Some(10), UVec2::new(4, 4));
+        let neighbour = &tiles[&UVec2::new(0, 0)];
+        assert_eq!(neighbour.index, 11);
+        assert_eq!(neighbour.properties, properties);
+        assert_eq!(tiles[&UVec2::new(1, 0)].index, 12);
+        assert!(tiles[&UVec2::new(1, 0)].properties.is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+//! Short messages in the bottom left corner, for example when saving a level failed.
+use bevy::{color::palettes::css::CRIMSON, prelude::*};
+
+use super::NORMAL_BUTTON;
+
+/// Seconds a notification stays visible
+const NOTIFICATION_TIME: f32 = 4.;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(show_notification)
+        .add_systems(Startup, spawn_notification_list)
+        .add_systems(Update, expire_notifications);
+}
+/// Shows a message for [`NOTIFICATION_TIME`] seconds. Triggered with [`Commands::trigger`]
+#[derive(Event)]
+pub struct Notification {
+    pub message: String,
+    pub error: bool,
+}
+impl Notification {
+    pub fn info(message: impl Into<String>) -> Notification {
+        Notification {
+            message: message.into(),
+            error: false,
+        }
+    }
+    pub fn error(message: impl Into<String>) -> Notification {
+        Notification {
+            message: message.into(),
+            error: true,
+        }
+    }
+}
+#[derive(Component)]
+struct NotificationList;
+#[derive(Component)]
+struct NotificationTimer(Timer);
+fn spawn_notification_list(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Notifications"),
+        Node {
+            left: Val::Percent(1.),
+            bottom: Val::Percent(2.),
+            row_gap: Val::Px(4.),
+            flex_direction: FlexDirection::Column,
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        Pickable::IGNORE,
+        NotificationList,
+    ));
+}
+fn show_notification(
+    trigger: Trigger<Notification>,
+    mut commands: Commands,
+    list: Single<Entity, With<NotificationList>>,
+) {
+    let notification = trigger.event();
+    let color = if notification.error {
+        warn!("{}", notification.message);
+        CRIMSON.into()
+    } else {
+        info!("{}", notification.message);
+        Color::WHITE
+    };
+    commands.entity(*list).with_child((
+        Text::new(notification.message.clone()),
+        TextFont::from_font_size(18.),
+        TextColor(color),
+        BackgroundColor(NORMAL_BUTTON.with_alpha(0.9)),
+        Node {
+            padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+            ..default()
+        },
+        NotificationTimer(Timer::from_seconds(NOTIFICATION_TIME, TimerMode::Once)),
+    ));
+}
+fn expire_notifications(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut notifications: Query<(Entity, &mut NotificationTimer)>,
+) {
+    for (e, mut timer) in &mut notifications {
+        timer.0.tick(time.delta());
+        if timer.0.finished() {
+            commands.entity(e).despawn();
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Playtest from the cursor. `F5` in edit mode starts the level with the player at the hovered tile,
+//! `F5` or `Escape` return to the editor.
+//!
+//! The level is stored before the playtest and spawned again once edit mode is back,
+//! so the player spawn, towers and enemies are exactly where they were.
+use bevy::prelude::*;
+
+use super::{
+    EditorEvents, EditorMeta, LevelTiles, UiRespawnTrigger, browser, notification::Notification,
+};
+use crate::{
+    entity::OnSpawnTrigger,
+    io::{self, SaveFile},
+    map::{self, LayerType, MousePosition},
+    screens::GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Playtest>()
+        .add_systems(
+            Update,
+            (restore_level, start_playtest.run_if(browser::not_typing))
+                .chain()
+                .run_if(in_state(GameState::Running).and(|meta: Res<EditorMeta>| meta.edit_mode)),
+        )
+        .add_systems(
+            Update,
+            stop_playtest.run_if(
+                in_state(GameState::Running)
+                    .and(|meta: Res<EditorMeta>| !meta.edit_mode)
+                    .and(browser::not_typing),
+            ),
+        );
+}
+/// The editor level while a playtest is running
+#[derive(Resource, Default)]
+pub struct Playtest {
+    snapshot: Option<Snapshot>,
+}
+impl Playtest {
+    pub fn is_active(&self) -> bool {
+        self.snapshot.is_some()
+    }
+}
+struct Snapshot {
+    level: SaveFile,
+    /// The level that is played. Loading another level ends the playtest without restoring
+    playtest_level: Handle<SaveFile>,
+}
+fn start_playtest(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_position: Res<MousePosition>,
+    level_tiles: LevelTiles,
+    textures: Res<map::Textures>,
+    mut editor_meta: ResMut<EditorMeta>,
+    mut save_files: ResMut<Assets<SaveFile>>,
+    mut asset_event_writer: EventWriter<AssetEvent<SaveFile>>,
+    mut playtest: ResMut<Playtest>,
+) {
+    if !keys.just_pressed(KeyCode::F5) {
+        return;
+    }
+    let Some(start) = mouse_position
+        .to_tilepos(&level_tiles.map_size)
+        .map(UVec2::from)
+    else {
+        return;
+    };
+    let mut level = level_tiles.to_save_file();
+    level.meta = editor_meta.level_meta.clone();
+    level.prefabs = editor_meta.prefab_instances.clone();
+    let mut playtest_level = level.clone();
+    let is_player = |layer_type: &LayerType, index: usize| {
+        textures.pack[layer_type].rules.iter().any(|rule| {
+            rule.target_index == index
+                && matches!(
+                    rule.on_spawn,
+                    OnSpawnTrigger::Player | OnSpawnTrigger::PlayerSpawnPlatform
+                )
+        })
+    };
+    // only the last player of a level is spawned
+    let mut player = None;
+    for (layer_type, layer) in &mut playtest_level.layers {
+        while let Some(i) = layer
+            .tiles
+            .iter()
+            .position(|tile| is_player(layer_type, tile.index))
+        {
+            player = Some((*layer_type, layer.tiles.remove(i)));
+        }
+    }
+    let player = player.or_else(|| {
+        let rule = textures.pack[&LayerType::Entities]
+            .rules
+            .iter()
+            .find(|rule| rule.on_spawn == OnSpawnTrigger::Player)?;
+        let tile = io::Tile {
+            pos: start,
+            index: rule.target_index,
+            properties: default(),
+        };
+        Some((LayerType::Entities, tile))
+    });
+    let Some((layer_type, player)) = player else {
+        commands.trigger(Notification::error("The tileset has no player"));
+        return;
+    };
+    let layer = playtest_level
+        .layers
+        .entry(layer_type)
+        .or_insert(io::Layer {
+            tiles: Vec::default(),
+        });
+    layer.tiles.retain(|tile| tile.pos != start);
+    layer.tiles.push(io::Tile {
+        pos: start,
+        ..player
+    });
+    let handle = save_files.add(playtest_level);
+    asset_event_writer.write(AssetEvent::Modified { id: handle.id() });
+    editor_meta.current_level = handle.clone();
+    editor_meta.edit_mode = false;
+    playtest.snapshot = Some(Snapshot {
+        level,
+        playtest_level: handle,
+    });
+    commands.trigger(UiRespawnTrigger::OverviewRespawn);
+    commands.trigger(UiRespawnTrigger::TileSelectionRemove);
+    commands.trigger(Notification::info("Press F5 to return to the editor"));
+}
+fn stop_playtest(
+    keys: Res<ButtonInput<KeyCode>>,
+    playtest: Res<Playtest>,
+    mut event_writer: EventWriter<EditorEvents>,
+) {
+    if playtest.is_active() && keys.any_just_pressed([KeyCode::F5, KeyCode::Escape]) {
+        event_writer.write(EditorEvents::ToggleEditMode);
+    }
+}
+/// Spawns the stored level once edit mode is back, no matter how it was switched on
+fn restore_level(
+    mut commands: Commands,
+    mut editor_meta: ResMut<EditorMeta>,
+    mut save_files: ResMut<Assets<SaveFile>>,
+    mut asset_event_writer: EventWriter<AssetEvent<SaveFile>>,
+    mut playtest: ResMut<Playtest>,
+) {
+    let Some(snapshot) = playtest.snapshot.take() else {
+        return;
+    };
+    if editor_meta.current_level != snapshot.playtest_level {
+        return;
+    }
+    let handle = save_files.add(snapshot.level);
+    asset_event_writer.write(AssetEvent::Modified { id: handle.id() });
+    editor_meta.current_level = handle;
+    // the overview was spawned without the save buttons while the playtest was running
+    commands.trigger(UiRespawnTrigger::OverviewRespawn);
+}
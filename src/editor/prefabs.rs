@@ -0,0 +1,210 @@
+//! Panel next to the tile selection that lists the prefabs in `assets/prefabs/`.
+//!
+//! Clicking a prefab attaches it to the cursor like a pasted stamp, see [`super::tools`].
+//! Placed prefabs stay linked to their file, so the level picks up changes to the prefab when it is loaded again.
+//! The link toggle places them as plain tiles instead. Editing the tiles of a placed prefab unlinks it.
+//! `Save copy` stores the copied tiles as a new prefab.
+
+use bevy::{asset::LoadedFolder, color::palettes::css::CRIMSON, prelude::*};
+
+use super::{
+    EditorMeta, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON, UiRespawnTrigger,
+    notification::Notification, tools::Stamp,
+};
+use crate::{
+    io::{self, Prefab},
+    screens::GameState,
+    widget::DEAD_BACKGROUND,
+};
+
+const PREFAB_FOLDER: &str = "prefabs";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PrefabLibrary>()
+        .add_observer(toggle_prefab_panel)
+        .add_systems(
+            Update,
+            (prefab_button_system, refresh_prefab_panel)
+                .run_if(in_state(GameState::Running).and(|meta: Res<EditorMeta>| meta.edit_mode)),
+        );
+}
+/// All prefabs of [`PREFAB_FOLDER`]
+#[derive(Resource)]
+struct PrefabLibrary {
+    /// Folders can not be listed on the web, so there are no prefabs
+    folder: Option<Handle<LoadedFolder>>,
+}
+impl FromWorld for PrefabLibrary {
+    fn from_world(world: &mut World) -> Self {
+        let folder = if cfg!(target_arch = "wasm32") {
+            None
+        } else {
+            Some(world.resource::<AssetServer>().load_folder(PREFAB_FOLDER))
+        };
+        PrefabLibrary { folder }
+    }
+}
+#[derive(Component)]
+struct PrefabPanelUiRoot;
+#[derive(Component)]
+enum PrefabButton {
+    Place(Handle<Prefab>),
+    SaveClipboard,
+    ToggleLinked,
+}
+fn toggle_prefab_panel(
+    trigger: Trigger<UiRespawnTrigger>,
+    mut commands: Commands,
+    library: Res<PrefabLibrary>,
+    folders: Res<Assets<LoadedFolder>>,
+    prefabs: Res<Assets<Prefab>>,
+    editor_meta: Res<EditorMeta>,
+    panels: Query<Entity, With<PrefabPanelUiRoot>>,
+) {
+    let close = match trigger.event() {
+        UiRespawnTrigger::TileSelectionRespawn | UiRespawnTrigger::PrefabPanelRespawn => false,
+        UiRespawnTrigger::TileSelectionRemove => true,
+        _ => return,
+    };
+    for e in &panels {
+        commands.entity(e).despawn();
+    }
+    if close {
+        return;
+    }
+    let handles = library
+        .folder
+        .as_ref()
+        .and_then(|folder| folders.get(folder.id()))
+        .map(|folder| &folder.handles[..])
+        .unwrap_or_default();
+    let mut entries: Vec<(String, Handle<Prefab>)> = handles
+        .iter()
+        .filter_map(|handle| {
+            let handle = handle.clone().try_typed::<Prefab>().ok()?;
+            let prefab = prefabs.get(handle.id())?;
+            let name = if prefab.name.is_empty() {
+                let path = handle.path()?.path();
+                path.file_name()?
+                    .to_str()?
+                    .trim_end_matches(".prefab.ron")
+                    .into()
+            } else {
+                prefab.name.clone()
+            };
+            Some((name, handle))
+        })
+        .collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    commands
+        .spawn((
+            Node {
+                right: Val::Percent(12.5),
+                top: Val::Percent(5.),
+                width: Val::Percent(12.),
+                padding: UiRect::all(Val::Px(8.)),
+                row_gap: Val::Px(6.),
+                flex_direction: FlexDirection::Column,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            BackgroundColor(DEAD_BACKGROUND.with_alpha(0.8)),
+            PrefabPanelUiRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(prefab_button(PrefabButton::SaveClipboard, "Save copy"));
+            let link = if editor_meta.place_unlinked {
+                "Place unlinked"
+            } else {
+                "Place linked"
+            };
+            parent.spawn(prefab_button(PrefabButton::ToggleLinked, link));
+            for (name, handle) in entries {
+                parent.spawn(prefab_button(PrefabButton::Place(handle), name));
+            }
+        });
+}
+fn prefab_button(button: PrefabButton, text: impl Into<String>) -> impl Bundle {
+    (
+        Button,
+        button,
+        Text::new(text),
+        TextFont::from_font_size(18.),
+        BackgroundColor(NORMAL_BUTTON),
+        Outline::new(Val::Px(2.0), Val::ZERO, CRIMSON.into()),
+        Node {
+            min_height: Val::Px(24.),
+            padding: UiRect::horizontal(Val::Px(4.)),
+            ..default()
+        },
+    )
+}
+fn prefab_button_system(
+    mut commands: Commands,
+    mut buttons: Query<(&Interaction, &PrefabButton, &mut Outline), Changed<Interaction>>,
+    mut editor_meta: ResMut<EditorMeta>,
+    prefabs: Res<Assets<Prefab>>,
+    library: Res<PrefabLibrary>,
+) {
+    for (interaction, button, mut outline) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                outline.color = PRESSED_BUTTON;
+                match button {
+                    PrefabButton::Place(handle) => {
+                        let (Some(prefab), Some(path)) = (prefabs.get(handle.id()), handle.path())
+                        else {
+                            continue;
+                        };
+                        editor_meta.clipboard = Some(Stamp::from_prefab(prefab, path.to_string()));
+                        editor_meta.pasting = true;
+                    }
+                    PrefabButton::SaveClipboard => {
+                        let Some(stamp) = &editor_meta.clipboard else {
+                            commands.trigger(Notification::info(
+                                "Copy a selection before saving it as prefab",
+                            ));
+                            continue;
+                        };
+                        let Some(path) = io::select_prefab_file() else {
+                            if library.folder.is_none() {
+                                commands.trigger(Notification::error(
+                                    "Prefabs can not be saved in the browser",
+                                ));
+                            }
+                            continue;
+                        };
+                        match io::write_prefab(&path, &stamp.to_prefab(String::new())) {
+                            Ok(()) => commands.trigger(Notification::info(format!(
+                                "Saved prefab {}",
+                                path.display()
+                            ))),
+                            Err(err) => commands.trigger(Notification::error(format!(
+                                "Could not save prefab {}: {err}",
+                                path.display()
+                            ))),
+                        }
+                    }
+                    PrefabButton::ToggleLinked => {
+                        editor_meta.place_unlinked = !editor_meta.place_unlinked;
+                        commands.trigger(UiRespawnTrigger::PrefabPanelRespawn);
+                    }
+                }
+            }
+            Interaction::Hovered => outline.color = HOVERED_BUTTON,
+            Interaction::None => outline.color = CRIMSON.into(),
+        }
+    }
+}
+/// Lists new and changed prefab files
+fn refresh_prefab_panel(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<Prefab>>,
+    mut folder_events: EventReader<AssetEvent<LoadedFolder>>,
+    panels: Query<(), With<PrefabPanelUiRoot>>,
+) {
+    let changed = events.read().count() + folder_events.read().count() > 0;
+    if changed && !panels.is_empty() {
+        commands.trigger(UiRespawnTrigger::PrefabPanelRespawn);
+    }
+}
@@ -0,0 +1,369 @@
+//! The ways tiles can be placed with the mouse, see [`EditorTool`].
+//!
+//! A region selected with [`EditorTool::Select`] is copied with `Ctrl+C` from the current layer
+//! or with `Ctrl+Shift+C` from all layers. `Ctrl+V` attaches the copy to the cursor as a [`Stamp`]
+//! which is placed on click. While pasting, `R` rotates the stamp, `F` flips it horizontally,
+//! `Shift+F` vertically and `Escape` stops pasting.
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    io::{Layer, Prefab, Tile},
+    map::LayerType,
+};
+
+use super::{EditorMeta, LevelTiles, browser};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        check_clipboard_shortcuts.run_if(
+            in_state(GameState::Running)
+                .and(|meta: Res<EditorMeta>| meta.edit_mode)
+                .and(browser::not_typing),
+        ),
+    );
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EditorTool {
+    /// Fills the dragged rectangle
+    #[default]
+    Rectangle,
+    /// Paints every tile the cursor moves over while the button is held
+    Freehand,
+    /// Paints a straight line from where the button was pressed to where it was released
+    Line,
+    /// Replaces the clicked tile and all connected tiles with the same index
+    FloodFill,
+    /// Removes every tile the cursor moves over while the button is held
+    Erase,
+    /// Selects a rectangle to copy
+    Select,
+}
+impl EditorTool {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EditorTool::Rectangle => "Rectangle",
+            EditorTool::Freehand => "Freehand",
+            EditorTool::Line => "Line",
+            EditorTool::FloodFill => "Fill",
+            EditorTool::Erase => "Erase",
+            EditorTool::Select => "Select",
+        }
+    }
+    pub fn next(&self) -> Self {
+        match self {
+            EditorTool::Rectangle => EditorTool::Freehand,
+            EditorTool::Freehand => EditorTool::Line,
+            EditorTool::Line => EditorTool::FloodFill,
+            EditorTool::FloodFill => EditorTool::Erase,
+            EditorTool::Erase => EditorTool::Select,
+            EditorTool::Select => EditorTool::Rectangle,
+        }
+    }
+    /// Tools that collect the tiles under the cursor while the button is held
+    pub fn is_stroke(&self) -> bool {
+        matches!(self, EditorTool::Freehand | EditorTool::Erase)
+    }
+}
+/// Positions connected to `start` that have the same index as `start` in `tiles`.
+/// Empty positions are connected to other empty positions. The region ends at the border of the map
+pub fn flood_fill(start: UVec2, map_size: UVec2, tiles: &HashMap<UVec2, usize>) -> Vec<UVec2> {
+    if start.cmpge(map_size).any() {
+        return Vec::new();
+    }
+    let index = tiles.get(&start);
+    let mut region = Vec::new();
+    let mut visited = HashSet::from([start]);
+    let mut open = vec![start];
+    while let Some(pos) = open.pop() {
+        region.push(pos);
+        let neighbours = [
+            pos.x.checked_sub(1).map(|x| UVec2::new(x, pos.y)),
+            pos.y.checked_sub(1).map(|y| UVec2::new(pos.x, y)),
+            Some(pos + UVec2::X),
+            Some(pos + UVec2::Y),
+        ];
+        for neighbour in neighbours.into_iter().flatten() {
+            if neighbour.cmplt(map_size).all()
+                && tiles.get(&neighbour) == index
+                && visited.insert(neighbour)
+            {
+                open.push(neighbour);
+            }
+        }
+    }
+    region
+}
+/// Tiles copied from a rectangle of the map.
+/// Positions are relative to the bottom left corner of the rectangle
+#[derive(Clone)]
+pub struct Stamp {
+    size: UVec2,
+    /// Every copied layer, even if it had no tiles in the rectangle
+    layers: Vec<(LayerType, Vec<Tile>)>,
+    /// Asset path of the prefab the stamp was made from.
+    /// Rotating or flipping turns it into plain tiles
+    prefab: Option<String>,
+}
+impl Stamp {
+    /// Copies the tiles of `layers` inside of `rect`
+    pub fn copy(level_tiles: &LevelTiles, rect: URect, layers: &[LayerType]) -> Stamp {
+        let level = level_tiles.to_save_file();
+        let layers = layers
+            .iter()
+            .map(|layer_type| {
+                let tiles = level
+                    .layers
+                    .get(layer_type)
+                    .map(|layer| &layer.tiles[..])
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|tile| rect.contains(tile.pos))
+                    .map(|tile| Tile {
+                        pos: tile.pos - rect.min,
+                        ..tile.clone()
+                    })
+                    .collect();
+                (*layer_type, tiles)
+            })
+            .collect();
+        Stamp {
+            size: rect.size() + UVec2::ONE,
+            layers,
+            prefab: None,
+        }
+    }
+    pub fn from_prefab(prefab: &Prefab, path: String) -> Stamp {
+        let layers = (0..3)
+            .map(LayerType::from_u8)
+            .filter_map(|layer_type| {
+                let layer = prefab.layers.get(&layer_type)?;
+                Some((layer_type, layer.tiles.clone()))
+            })
+            .collect();
+        Stamp {
+            size: prefab.size.max(UVec2::ONE),
+            layers,
+            prefab: Some(path),
+        }
+    }
+    /// Turns the copied tiles into a prefab
+    pub fn to_prefab(&self, name: String) -> Prefab {
+        let layers = self
+            .layers
+            .iter()
+            .map(|(layer_type, tiles)| {
+                let layer = Layer {
+                    tiles: tiles.clone(),
+                };
+                (*layer_type, layer)
+            })
+            .collect();
+        Prefab {
+            name,
+            size: self.size,
+            layers,
+        }
+    }
+    /// Width and height in tiles
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+    pub fn prefab(&self) -> Option<&str> {
+        self.prefab.as_deref()
+    }
+    /// Rotates the stamp by 90 degrees clockwise.
+    /// Only the arrangement is rotated, the textures of the tiles stay upright
+    pub fn rotate(&mut self) {
+        let size = self.size;
+        self.transform(|pos| UVec2::new(pos.y, size.x - 1 - pos.x));
+        self.size = UVec2::new(size.y, size.x);
+    }
+    pub fn flip_x(&mut self) {
+        let size = self.size;
+        self.transform(|pos| UVec2::new(size.x - 1 - pos.x, pos.y));
+    }
+    pub fn flip_y(&mut self) {
+        let size = self.size;
+        self.transform(|pos| UVec2::new(pos.x, size.y - 1 - pos.y));
+    }
+    fn transform(&mut self, f: impl Fn(UVec2) -> UVec2) {
+        self.prefab = None;
+        for (_, tiles) in &mut self.layers {
+            for tile in tiles {
+                tile.pos = f(tile.pos);
+            }
+        }
+    }
+    /// Every position covered by the stamp if its bottom left corner is at `origin`
+    pub fn footprint(&self, origin: UVec2) -> impl Iterator<Item = UVec2> + use<> {
+        let size = self.size;
+        (0..size.x).flat_map(move |x| (0..size.y).map(move |y| origin + UVec2::new(x, y)))
+    }
+    /// The tiles of every layer moved to `origin`
+    pub fn place(&self, origin: UVec2) -> Vec<(LayerType, Vec<Tile>)> {
+        self.layers
+            .iter()
+            .map(|(layer_type, tiles)| {
+                let tiles = tiles
+                    .iter()
+                    .map(|tile| Tile {
+                        pos: tile.pos + origin,
+                        ..tile.clone()
+                    })
+                    .collect();
+                (*layer_type, tiles)
+            })
+            .collect()
+    }
+}
+fn check_clipboard_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut editor_meta: ResMut<EditorMeta>,
+    level_tiles: LevelTiles,
+) {
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if ctrl && keys.just_pressed(KeyCode::KeyC) {
+        let Some(selection) = editor_meta.selection else {
+            info!("nothing selected to copy");
+            return;
+        };
+        let layers = if shift {
+            (0..3).map(LayerType::from_u8).collect()
+        } else {
+            vec![editor_meta.layer_type]
+        };
+        editor_meta.clipboard = Some(Stamp::copy(&level_tiles, selection, &layers));
+        info!("copied {} layers", layers.len());
+    }
+    if ctrl && keys.just_pressed(KeyCode::KeyV) && editor_meta.clipboard.is_some() {
+        editor_meta.pasting = true;
+    }
+    if !editor_meta.pasting || ctrl {
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        editor_meta.pasting = false;
+    }
+    let Some(stamp) = &mut editor_meta.clipboard else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::KeyR) {
+        stamp.rotate();
+    }
+    if keys.just_pressed(KeyCode::KeyF) {
+        if shift {
+            stamp.flip_y();
+        } else {
+            stamp.flip_x();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3x3 map with a wall of index 5 in the middle column
+    fn wall() -> HashMap<UVec2, usize> {
+        (0..3).map(|y| (UVec2::new(1, y), 5)).collect()
+    }
+    fn sorted(mut positions: Vec<UVec2>) -> Vec<UVec2> {
+        positions.sort_by_key(|pos| (pos.x, pos.y));
+        positions
+    }
+    #[test]
+    fn fills_empty_region_up_to_the_wall() {
+        let region = flood_fill(UVec2::ZERO, UVec2::splat(3), &wall());
+        assert_eq!(
+            sorted(region),
+            [UVec2::new(0, 0), UVec2::new(0, 1), UVec2::new(0, 2)]
+        );
+    }
+    #[test]
+    fn fills_connected_tiles_with_the_same_index() {
+        let mut tiles = wall();
+        tiles.insert(UVec2::new(2, 2), 5);
+        tiles.insert(UVec2::new(2, 0), 4);
+        let region = flood_fill(UVec2::new(1, 1), UVec2::splat(3), &tiles);
+        assert_eq!(
+            sorted(region),
+            [
+                UVec2::new(1, 0),
+                UVec2::new(1, 1),
+                UVec2::new(1, 2),
+                UVec2::new(2, 2)
+            ]
+        );
+    }
+    #[test]
+    fn does_not_fill_outside_of_the_map() {
+        assert!(flood_fill(UVec2::new(3, 0), UVec2::splat(3), &wall()).is_empty());
+        let region = flood_fill(UVec2::new(2, 0), UVec2::new(3, 2), &wall());
+        assert_eq!(sorted(region), [UVec2::new(2, 0), UVec2::new(2, 1)]);
+    }
+    /// A 2x1 prefab with index 1 on the left and 2 on the right
+    fn stamp() -> Stamp {
+        let tiles = [(0, 1), (1, 2)]
+            .map(|(x, index)| Tile {
+                pos: UVec2::new(x, 0),
+                index,
+                ..default()
+            })
+            .into();
+        let prefab = Prefab {
+            size: UVec2::new(2, 1),
+            layers: HashMap::from([(LayerType::Fg, Layer { tiles })]),
+            ..default()
+        };
+        Stamp::from_prefab(&prefab, "prefabs/bar.prefab.ron".into())
+    }
+    fn tiles(stamp: &Stamp) -> Vec<(UVec2, usize)> {
+        stamp
+            .place(UVec2::ZERO)
+            .into_iter()
+            .flat_map(|(_, tiles)| tiles)
+            .map(|tile| (tile.pos, tile.index))
+            .collect()
+    }
+    #[test]
+    fn rotates_clockwise() {
+        let mut stamp = stamp();
+        stamp.rotate();
+        assert_eq!(stamp.size(), UVec2::new(1, 2));
+        assert_eq!(
+            tiles(&stamp),
+            [(UVec2::new(0, 1), 1), (UVec2::new(0, 0), 2)]
+        );
+        // a rotated prefab is no longer the prefab
+        assert_eq!(stamp.prefab(), None);
+        stamp.rotate();
+        stamp.rotate();
+        stamp.rotate();
+        assert_eq!(
+            tiles(&stamp),
+            [(UVec2::new(0, 0), 1), (UVec2::new(1, 0), 2)]
+        );
+    }
+    #[test]
+    fn flips_in_place() {
+        let mut stamp = stamp();
+        stamp.flip_x();
+        assert_eq!(
+            tiles(&stamp),
+            [(UVec2::new(1, 0), 1), (UVec2::new(0, 0), 2)]
+        );
+        stamp.flip_y();
+        assert_eq!(stamp.size(), UVec2::new(2, 1));
+        assert_eq!(
+            tiles(&stamp),
+            [(UVec2::new(1, 0), 1), (UVec2::new(0, 0), 2)]
+        );
+        assert_eq!(stamp.place(UVec2::new(3, 4))[0].1[0].pos, UVec2::new(4, 4));
+    }
+}
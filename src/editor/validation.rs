@@ -0,0 +1,167 @@
+//! Checks the level while editing, see [`crate::validate`].
+//!
+//! Problems at a tile are marked in the level, all problems are listed on the left.
+use std::collections::HashMap;
+
+use bevy::{
+    color::palettes::css::{CRIMSON, ORANGE},
+    prelude::*,
+};
+
+use super::{EditorMeta, LevelTiles, NORMAL_BUTTON};
+use crate::{
+    combat::ENEMY_EXPLOSION_RADIUS,
+    map::{self, MapSize, TILEMAP_OFFSET, TILESIZE},
+    screens::GameState,
+    utils::tile_to_world,
+    validate::{self, Problem, ProblemKind, Severity, TilesetInfo},
+};
+
+/// Seconds between two checks
+const CHECK_INTERVAL: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LevelProblems>()
+        .add_systems(Startup, spawn_problem_list)
+        .add_systems(
+            Update,
+            show_problem_list.run_if(in_state(GameState::Running)),
+        )
+        .add_systems(
+            Update,
+            (check_level, draw_problem_markers)
+                .chain()
+                .run_if(in_state(GameState::Running).and(|meta: Res<EditorMeta>| meta.edit_mode)),
+        );
+}
+/// Problems of the current level, checked every [`CHECK_INTERVAL`] seconds
+#[derive(Resource)]
+struct LevelProblems {
+    problems: Vec<Problem>,
+    timer: Timer,
+}
+impl Default for LevelProblems {
+    fn default() -> Self {
+        LevelProblems {
+            problems: Vec::new(),
+            timer: Timer::from_seconds(CHECK_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+#[derive(Component)]
+struct ProblemList;
+fn spawn_problem_list(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Problems"),
+        Node {
+            left: Val::Percent(1.),
+            top: Val::Percent(20.),
+            max_width: Val::Percent(25.),
+            row_gap: Val::Px(4.),
+            flex_direction: FlexDirection::Column,
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        Pickable::IGNORE,
+        Visibility::Hidden,
+        ProblemList,
+    ));
+}
+/// The list is only shown in edit mode
+fn show_problem_list(
+    editor_meta: Res<EditorMeta>,
+    mut list: Single<&mut Visibility, With<ProblemList>>,
+) {
+    let visibility = if editor_meta.edit_mode {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    list.set_if_neq(visibility);
+}
+fn check_level(
+    mut commands: Commands,
+    mut level_problems: ResMut<LevelProblems>,
+    time: Res<Time>,
+    level_tiles: LevelTiles,
+    textures: Res<map::Textures>,
+    texture_atlas_layouts: Res<Assets<TextureAtlasLayout>>,
+    list: Single<Entity, With<ProblemList>>,
+) {
+    if !level_problems.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let tilesets: HashMap<_, _> = textures
+        .pack
+        .iter()
+        .filter_map(|(layer_type, pack)| {
+            let layout = texture_atlas_layouts.get(pack.layout.id())?;
+            let tileset = TilesetInfo {
+                tile_count: layout.len(),
+                rules: pack.rules.clone(),
+            };
+            Some((*layer_type, tileset))
+        })
+        .collect();
+    let problems = validate::validate_level(&level_tiles.to_save_file(), &tilesets);
+    let unchanged = problems
+        .iter()
+        .map(|problem| &problem.message)
+        .eq(level_problems
+            .problems
+            .iter()
+            .map(|problem| &problem.message));
+    if unchanged {
+        return;
+    }
+    commands
+        .entity(*list)
+        .despawn_related::<Children>()
+        .with_children(|parent| {
+            for problem in &problems {
+                parent.spawn((
+                    Text::new(problem.message.clone()),
+                    TextFont::from_font_size(16.),
+                    TextColor(problem_color(problem).into()),
+                    BackgroundColor(NORMAL_BUTTON.with_alpha(0.9)),
+                    Node {
+                        padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+                        ..default()
+                    },
+                ));
+            }
+        });
+    level_problems.problems = problems;
+}
+fn draw_problem_markers(
+    mut my_gizmos: Gizmos<DefaultGizmoConfigGroup>,
+    level_problems: Res<LevelProblems>,
+    map_size: Res<MapSize>,
+) {
+    for problem in &level_problems.problems {
+        let Some(pos) = problem.pos else {
+            continue;
+        };
+        let center = tile_to_world(&pos.into(), map_size.0, TILEMAP_OFFSET.extend(0.)).xy();
+        let color = problem_color(problem);
+        my_gizmos.rect_2d(
+            Isometry2d::from_translation(center),
+            Vec2::splat(TILESIZE as f32),
+            color,
+        );
+        // enemies have to explode inside the circle to light the tower
+        if problem.kind == ProblemKind::UnlitTower {
+            my_gizmos.circle_2d(
+                Isometry2d::from_translation(center),
+                ENEMY_EXPLOSION_RADIUS,
+                color.with_alpha(0.4),
+            );
+        }
+    }
+}
+fn problem_color(problem: &Problem) -> Srgba {
+    match problem.severity {
+        Severity::Error => CRIMSON,
+        Severity::Warning => ORANGE,
+    }
+}
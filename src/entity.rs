@@ -1,11 +1,10 @@
 use crate::{
-    animation::{self, Action, AnimationConfig, EnemyAnimation, PlayerAnimation},
+    animation::{self, AnimationSet, EnemyAnimation, PlayerAnimation, TowerAnimation},
     combat::Tame,
     editor::{RemoveOnLevelSwap, SaveOverride},
-    io,
-    map::{self, ENEMYSIZE, LayerType, TILESIZE},
-    movement::CollisionLayer,
-    screens::GameState,
+    io::{self, TileProperty},
+    map::{self, ENEMYSIZE, LayerType, MapSize, TILESIZE},
+    movement::{ACTIVE_TIME, CollisionLayer},
     utils::tile_to_world,
 };
 use avian2d::prelude::{
@@ -13,16 +12,13 @@ use avian2d::prelude::{
 };
 use bevy::prelude::*;
 use bevy_ecs_tilemap::tiles::{TilePos, TileStorage};
+use std::{collections::BTreeMap, time::Duration};
 
 pub fn plugin(app: &mut App) {
     app.add_observer(apply_rule)
-        .init_resource::<TowerCountdown>()
-        .add_systems(
-            Update,
-            (check_enemy_spawn).run_if(in_state(GameState::Running)),
-        );
+        .init_resource::<TowerCountdown>();
 }
-#[derive(Reflect, Clone, Copy, Debug)]
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
 pub enum OnSpawnTrigger {
     Player,
     PlayerSpawnPlatform,
@@ -33,18 +29,77 @@ pub enum OnSpawnTrigger {
     Portal,
     GameFinishedPlatform,
 }
-#[derive(Reflect, Event, Debug, Clone, Copy)]
+/// Describes what happens when a tile with `target_index` is spawned.
+/// Defined per tileset, see [`map::Tileset`]
+#[derive(Reflect, Event, Debug, Clone, Copy, serde::Deserialize)]
 pub struct Rule {
+    #[serde(rename = "index")]
     pub target_index: usize,
+    #[serde(default = "spawn_in_tilemap_default")]
     pub spawn_in_tilemap: bool,
+    #[serde(rename = "trigger")]
     pub on_spawn: OnSpawnTrigger,
+    /// Only used by triggers that spawn a collider
+    #[serde(default)]
+    pub collider: TileCollider,
+    /// Only used by [`OnSpawnTrigger::Pit`]
+    #[serde(default)]
+    pub can_dash_over: bool,
+}
+fn spawn_in_tilemap_default() -> bool {
+    true
+}
+/// Shape of the collider of a tile. Sizes are in pixel
+#[derive(Reflect, Debug, Clone, Copy, Default, serde::Deserialize)]
+pub enum TileCollider {
+    /// Covers the whole tile
+    #[default]
+    Full,
+    Rectangle {
+        width: f32,
+        height: f32,
+    },
+    Circle {
+        radius: f32,
+    },
 }
-impl Rule {
-    pub fn new(target_index: usize, on_spawn: OnSpawnTrigger, spawn_in_tilemap: bool) -> Rule {
-        Rule {
-            target_index,
-            spawn_in_tilemap,
-            on_spawn,
+impl TileCollider {
+    pub fn collider(&self) -> avian::Collider {
+        match *self {
+            TileCollider::Full => avian::Collider::rectangle(TILESIZE as f32, TILESIZE as f32),
+            TileCollider::Rectangle { width, height } => avian::Collider::rectangle(width, height),
+            TileCollider::Circle { radius } => avian::Collider::circle(radius),
+        }
+    }
+}
+/// Properties of a single tile, see [`io::Tile::properties`].
+/// [`apply_rule`] reads the `speed` of enemies, the `timer` of towers in seconds
+/// and whether the player `can_dash_over` a pit
+#[derive(Component, Clone, Default, Debug)]
+pub struct TileProperties(pub BTreeMap<String, TileProperty>);
+impl TileProperties {
+    pub fn float(&self, key: &str) -> Option<f32> {
+        match self.0.get(key)? {
+            TileProperty::Int(value) => Some(*value as f32),
+            TileProperty::Float(value) => Some(*value as f32),
+            other => {
+                warn!("expected a number for property '{key}' but got {other:?}");
+                None
+            }
+        }
+    }
+    pub fn seconds(&self, key: &str) -> Option<Duration> {
+        self.float(key)
+            .filter(|seconds| *seconds >= 0.)
+            .map(Duration::from_secs_f32)
+    }
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        match self.0.get(key)? {
+            TileProperty::Bool(value) => Some(*value),
+            other => {
+                warn!("expected a bool for property '{key}' but got {other:?}");
+                None
+            }
         }
     }
 }
@@ -59,11 +114,14 @@ fn apply_rule(
     tile_positions: Query<&TilePos>,
     player_spawn_platforms: Query<Entity, With<PlayerSpawnPlatform>>,
     textures: Res<map::Textures>,
+    map_size: Res<MapSize>,
     mut tile_map: Query<(Entity, &LayerType), With<TileStorage>>,
     players: Query<(Entity, &ChildOf), With<Player>>,
+    tile_properties: Query<&TileProperties>,
 ) {
     let entity = trigger.target();
     let rule = trigger.event();
+    let properties = tile_properties.get(entity).cloned().unwrap_or_default();
     let trigger = rule.on_spawn;
     let (entities_tilemap_e, _) = tile_map
         .iter_mut()
@@ -73,7 +131,8 @@ fn apply_rule(
     match trigger {
         OnSpawnTrigger::Tower => {
             let tile_pos = tile_positions.get(entity).unwrap();
-            let mut tower_position = tile_to_world(tile_pos, entities_tilemap_translation);
+            let mut tower_position =
+                tile_to_world(tile_pos, map_size.0, entities_tilemap_translation);
             tower_position.y += 4.;
             let sprite = Sprite::from_atlas_image(
                 textures.fire.texture.clone(),
@@ -85,21 +144,28 @@ fn apply_rule(
             let tile = io::Tile {
                 pos: tile_pos.into(),
                 index: rule.target_index,
+                properties: properties.0.clone(),
             };
+            // the fire is a child of the tile, so it is removed together with the tile in the editor
+            commands.entity(entity).insert(Transform::default());
             commands.spawn((
                 RemoveOnLevelSwap,
                 sprite,
-                tower_spawn(),
+                tower_spawn(
+                    properties.seconds("timer"),
+                    textures.fire_animations.clone(),
+                ),
                 Transform::from_translation(tower_position),
                 SaveOverride(tile),
+                ChildOf(entity),
             ));
         }
         OnSpawnTrigger::Collider => {
             let tile_pos = tile_positions.get(entity).unwrap();
-            let position = tile_to_world(tile_pos, entities_tilemap_translation);
+            let position = tile_to_world(tile_pos, map_size.0, entities_tilemap_translation);
             commands.entity(entity).insert((
                 avian::RigidBody::Static,
-                avian::Collider::rectangle(TILESIZE as f32, TILESIZE as f32),
+                rule.collider.collider(),
                 Transform::from_translation(position),
             ));
         }
@@ -117,7 +183,7 @@ fn apply_rule(
             } else {
                 entity
             };
-            let player_position = tile_to_world(tile_pos, entities_tilemap_translation);
+            let player_position = tile_to_world(tile_pos, map_size.0, entities_tilemap_translation);
             commands
                 .spawn((
                     PlayerController,
@@ -139,18 +205,19 @@ fn apply_rule(
             let tile = io::Tile {
                 pos: tile_pos.into(),
                 index: rule.target_index,
+                properties: properties.0.clone(),
             };
             commands.entity(entity).insert((
                 Transform::from_translation(Vec3::Y * 10.),
                 RemoveOnLevelSwap,
                 sprite,
-                player_spawn(),
+                player_spawn(textures.player_animations.clone()),
                 SaveOverride(tile),
             ));
         }
         OnSpawnTrigger::Enemy => {
             let tile_pos = tile_positions.get(entity).unwrap();
-            let enemy_position = tile_to_world(tile_pos, entities_tilemap_translation);
+            let enemy_position = tile_to_world(tile_pos, map_size.0, entities_tilemap_translation);
             let sprite = Sprite::from_atlas_image(
                 textures.enemy.texture.clone(),
                 TextureAtlas {
@@ -161,52 +228,58 @@ fn apply_rule(
             let tile = io::Tile {
                 pos: tile_pos.into(),
                 index: rule.target_index,
+                properties: properties.0.clone(),
             };
             commands.entity(entity).insert((
                 RemoveOnLevelSwap,
                 Transform::from_translation(enemy_position),
                 sprite,
-                enemy_spawn(),
+                enemy_spawn(
+                    properties.float("speed").unwrap_or(3000.),
+                    textures.enemy_animations.clone(),
+                ),
                 SaveOverride(tile),
             ));
         }
         OnSpawnTrigger::Pit => {
             let tile_pos = tile_positions.get(entity).unwrap();
-            let position = tile_to_world(tile_pos, entities_tilemap_translation);
+            let position = tile_to_world(tile_pos, map_size.0, entities_tilemap_translation);
             commands.entity(entity).insert((
                 Transform::from_translation(position),
                 Pit {
-                    can_dash_over: false,
+                    can_dash_over: properties
+                        .bool("can_dash_over")
+                        .unwrap_or(rule.can_dash_over),
                 },
                 avian::RigidBody::Static,
                 Sensor,
-                avian::Collider::rectangle(TILESIZE as f32, TILESIZE as f32),
+                rule.collider.collider(),
                 CollisionEventsEnabled,
                 CollidingEntities::default(),
             ));
         }
         OnSpawnTrigger::Portal => {
             let tile_pos = tile_positions.get(entity).unwrap();
-            let position = tile_to_world(tile_pos, entities_tilemap_translation);
+            let position = tile_to_world(tile_pos, map_size.0, entities_tilemap_translation);
             commands.entity(entity).insert((
                 Transform::from_translation(position),
                 Portal::Closed,
                 avian::RigidBody::Static,
                 Sensor,
-                avian::Collider::rectangle(TILESIZE as f32, TILESIZE as f32),
+                rule.collider.collider(),
                 CollisionEventsEnabled,
                 CollidingEntities::default(),
             ));
         }
         OnSpawnTrigger::GameFinishedPlatform => {
             let tile_pos = tile_positions.get(entity).unwrap();
-            let position = tile_to_world(tile_pos, entities_tilemap_translation);
+            let position = tile_to_world(tile_pos, map_size.0, entities_tilemap_translation);
             commands.entity(entity).insert((
                 Transform::from_translation(position),
                 Portal::Open,
                 avian::RigidBody::Static,
                 Sensor,
-                avian::Collider::rectangle(TILESIZE as f32, TILESIZE as f32),
+                rule.collider.collider(),
                 CollisionEventsEnabled,
                 CollidingEntities::default(),
             ));
@@ -214,10 +287,21 @@ fn apply_rule(
     };
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource)]
 pub struct TowerCountdown {
     pub timer: Option<Timer>,
     pub level_complete: bool,
+    /// How long towers keep burning in the current level
+    pub duration: Duration,
+}
+impl Default for TowerCountdown {
+    fn default() -> Self {
+        TowerCountdown {
+            timer: None,
+            level_complete: false,
+            duration: ACTIVE_TIME,
+        }
+    }
 }
 #[derive(Component)]
 pub struct GameFinishPortal;
@@ -226,20 +310,26 @@ pub struct Tower {
     // pub active: Option<Timer>,
     pub active: bool,
     pub activatable: bool,
+    /// Overrides [`TowerCountdown::duration`] when this tower is lit
+    pub burn_time: Option<Duration>,
 }
 impl Default for Tower {
     fn default() -> Self {
         Tower {
             active: false,
             activatable: true,
+            burn_time: None,
         }
     }
 }
-fn tower_spawn() -> impl Bundle {
+fn tower_spawn(burn_time: Option<Duration>, animations: Handle<AnimationSet>) -> impl Bundle {
     (
-        Tower::default(),
+        Tower {
+            burn_time,
+            ..default()
+        },
         Visibility::Hidden,
-        AnimationConfig::new(0..4, 2),
+        animation::animation_bundle(TowerAnimation::Burning, animations),
     )
 }
 #[derive(Component, PartialEq, Eq)]
@@ -271,21 +361,21 @@ impl Player {
         }
     }
 }
-fn player_spawn() -> impl Bundle {
+fn player_spawn(animations: Handle<AnimationSet>) -> impl Bundle {
     (
         Player::new(3000.),
         CollisionLayers::new(CollisionLayer::Player, CollisionLayer::Block),
-        animation::animation_bundle(PlayerAnimation::Idle),
+        animation::animation_bundle(PlayerAnimation::Idle, animations),
     )
 }
 #[derive(Component)]
 pub struct Enemy {
     pub speed: f32,
 }
-fn enemy_spawn() -> impl Bundle {
+fn enemy_spawn(speed: f32, animations: Handle<AnimationSet>) -> impl Bundle {
     (
-        Enemy { speed: 3000. },
-        animation::animation_bundle(EnemyAnimation::Spawn),
+        Enemy { speed },
+        animation::animation_bundle(EnemyAnimation::Spawn, animations),
         avian::RigidBody::Dynamic,
         avian::LinearVelocity::ZERO,
         CollisionLayers::new(
@@ -299,14 +389,3 @@ fn enemy_spawn() -> impl Bundle {
 }
 #[derive(Component)]
 pub struct Flag;
-fn check_enemy_spawn(mut enemies: Query<(&mut EnemyAnimation, &Sprite, &Visibility)>) {
-    for (mut animation, sprite, visibility) in &mut enemies {
-        if *animation == EnemyAnimation::Spawn
-            && *visibility != Visibility::Hidden
-            && sprite.texture_atlas.as_ref().unwrap().index
-                == animation.as_animation().last_sprite_index()
-        {
-            *animation = EnemyAnimation::Idle;
-        }
-    }
-}
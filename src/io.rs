@@ -1,13 +1,108 @@
-use crate::map::LayerType;
+use crate::{
+    campaign::Campaign,
+    map::{LayerType, TILEMAP_MAPSIZE, TILESIZE, Tileset, TilesetDef},
+    storage,
+};
 use bevy::{
     asset::{AssetLoader, LoadContext, io::Reader},
     prelude::*,
 };
 use ron::ser::PrettyConfig;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+mod migrations;
+
+/// Asset path of a level as used by [`EditorEvents::LoadLevel`](crate::editor::EditorEvents::LoadLevel).
+/// 'level1' becomes 'level/level1.ron'. Names with an extension like 'room.tmx' are kept as they are
+pub fn level_path(name: &str) -> PathBuf {
+    let path = PathBuf::from("level").join(name);
+    if path.extension().is_some() {
+        path
+    } else {
+        path.with_extension("ron")
+    }
+}
+/// Inverse of [`level_path`]. [`None`] for files outside of the level folder
+pub fn level_name(path: &Path) -> Option<String> {
+    let name = path.strip_prefix("level").ok()?.to_str()?;
+    let name = name.strip_suffix(".ron").unwrap_or(name);
+    Some(name.replace('\\', "/"))
+}
+/// All `.ron` levels directly inside `dir`, sorted by path
+#[cfg(not(target_arch = "wasm32"))]
+pub fn level_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = std::fs::read_dir(dir)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    Ok(paths)
+}
+/// Asset path of the [`LevelIndex`]
+pub const LEVEL_INDEX_PATH: &str = "levels.index.ron";
+/// The levels of the level folder by the same name as in [`EditorEvents::LoadLevel`](crate::editor::EditorEvents::LoadLevel).
+/// Folders can not be listed on the web, so the level browser reads this list instead
+#[derive(Asset, TypePath, serde::Serialize, serde::Deserialize, Clone, Default, Debug)]
+pub struct LevelIndex {
+    pub levels: Vec<String>,
+}
+impl LevelIndex {
+    /// Appends the levels of `bundled` that are missing.
+    /// On the web the stored index replaces the bundled one, so this picks up levels added to later builds
+    pub fn merge(&mut self, bundled: LevelIndex) {
+        for level in bundled.levels {
+            if !self.levels.contains(&level) {
+                self.levels.push(level);
+            }
+        }
+    }
+}
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("Could not write file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not serialize RON: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("Could not use the storage of the browser: {0}")]
+    Browser(String),
+}
+/// The level as it is written to disk
+pub fn save_file_to_string(file: &SaveFile) -> Result<String, ron::Error> {
+    ron::ser::to_string_pretty(file, PrettyConfig::new().compact_structs(true))
+}
+/// Writes `file` to the asset path `path`, see [`storage::Storage`]
+pub fn write_save_file(path: &Path, file: &SaveFile) -> Result<(), SaveError> {
+    storage::storage().write(path, &save_file_to_string(file)?)
+}
+/// Writes `file` as the level called `name`, see [`level_path`]
+pub fn write_level(name: &str, file: &SaveFile) -> Result<(), SaveError> {
+    write_save_file(&level_path(name), file)
+}
+/// Writes `file`, the loaded level called `from`, under the name `to` and removes `from`
+pub fn rename_level(from: &str, to: &str, file: &SaveFile) -> Result<(), SaveError> {
+    let storage = storage::storage();
+    if storage.exists(&level_path(to)) {
+        return Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists).into());
+    }
+    write_level(to, file)?;
+    storage.remove(&level_path(from))
+}
+pub fn delete_level(name: &str) -> Result<(), SaveError> {
+    storage::storage().remove(&level_path(name))
+}
+pub fn write_level_index(index: &LevelIndex) -> Result<(), SaveError> {
+    let file_string = ron::ser::to_string_pretty(index, PrettyConfig::new())?;
+    storage::storage().write(Path::new(LEVEL_INDEX_PATH), &file_string)
+}
+/// Path relative to the `assets` folder. [`None`] for files outside of it
+#[cfg(not(target_arch = "wasm32"))]
+fn asset_path(path: &Path) -> Option<PathBuf> {
+    let mut components = path.iter();
+    components.find(|component| *component == "assets")?;
+    Some(components.collect())
+}
 #[cfg(target_arch = "wasm32")]
 pub fn select_file() -> Option<PathBuf> {
     None
@@ -15,44 +110,185 @@ pub fn select_file() -> Option<PathBuf> {
 #[cfg(not(target_arch = "wasm32"))]
 pub fn select_file() -> Option<PathBuf> {
     let fd = rfd::FileDialog::new()
-        .add_filter("level", &["ron"])
+        .add_filter("level", &["ron", "tmx", "tmj", "ldtk"])
         .set_directory("assets/");
-    fd.pick_file().map(|path| {
-        path.iter()
-            .skip_while(|p| p.to_str().unwrap() != "assets")
-            .skip(1)
-            .collect()
-    })
+    fd.pick_file().and_then(|path| asset_path(&path))
 }
 #[cfg(target_arch = "wasm32")]
-pub fn save(file: &SaveFile) {
-    let file_string =
-        ron::ser::to_string_pretty(file, PrettyConfig::new().compact_structs(true)).unwrap();
-    println!("{file_string}");
+pub fn select_save_file(_file_name: &str) -> Option<PathBuf> {
+    None
 }
+/// Asks where a level should be saved. Returns the asset path of the picked file
 #[cfg(not(target_arch = "wasm32"))]
-pub fn save(file: &SaveFile) {
-    use rfd::FileDialog;
-    let file_string =
-        ron::ser::to_string_pretty(file, PrettyConfig::new().compact_structs(true)).unwrap();
-
-    let path = FileDialog::new()
+pub fn select_save_file(file_name: &str) -> Option<PathBuf> {
+    let path = rfd::FileDialog::new()
         .add_filter("ron", &["ron"])
-        .set_directory("assets")
-        .set_file_name("level.ron")
-        .save_file();
-
-    if let Some(path) = path {
-        std::fs::write(path, file_string).unwrap();
-        info!("Saved successfully");
-    } else {
-        info!("Save was aborted since no file was selected");
+        .set_directory("assets/level")
+        .set_file_name(file_name)
+        .save_file()?;
+    let asset_path = asset_path(&path);
+    if asset_path.is_none() {
+        warn!("levels have to be saved inside of the assets folder, not at {path:?}");
     }
+    asset_path
 }
+#[cfg(target_arch = "wasm32")]
+pub fn select_prefab_file() -> Option<PathBuf> {
+    None
+}
+/// Asks where a prefab should be saved. Returns the asset path of the picked file
+#[cfg(not(target_arch = "wasm32"))]
+pub fn select_prefab_file() -> Option<PathBuf> {
+    let path = rfd::FileDialog::new()
+        .add_filter("prefab", &["ron"])
+        .set_directory("assets/prefabs")
+        .set_file_name("prefab.prefab.ron")
+        .save_file()?;
+    let asset_path = asset_path(&path);
+    if asset_path.is_none() {
+        warn!("prefabs have to be saved inside of the assets folder, not at {path:?}");
+    }
+    asset_path
+}
+/// Writes `prefab` to the asset path `path`, see [`storage::Storage`]
+pub fn write_prefab(path: &Path, prefab: &Prefab) -> Result<(), SaveError> {
+    let file_string =
+        ron::ser::to_string_pretty(prefab, PrettyConfig::new().compact_structs(true))?;
+    storage::storage().write(path, &file_string)
+}
+
+/// Version of the level format written by [`write_save_file`].
+/// Bump it together with a new version in [`migrations`] whenever the format changes.
+pub const SAVE_FILE_VERSION: u32 = 5;
 
-#[derive(Asset, TypePath, serde::Serialize, serde::Deserialize, Default)]
+#[derive(Asset, TypePath, serde::Serialize, serde::Deserialize, Clone)]
 pub struct SaveFile {
+    /// Files written before the format was versioned have no header and count as version 0.
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub meta: LevelMeta,
+    /// Width and height of the map in tiles
+    #[serde(default = "default_map_size")]
+    pub size: UVec2,
     pub layers: HashMap<LayerType, Layer>,
+    /// Prefabs placed in the level. Their tiles are also stored in `layers`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prefabs: Vec<PrefabInstance>,
+}
+fn default_map_size() -> UVec2 {
+    TILEMAP_MAPSIZE
+}
+impl Default for SaveFile {
+    fn default() -> Self {
+        SaveFile {
+            version: SAVE_FILE_VERSION,
+            meta: LevelMeta::default(),
+            size: TILEMAP_MAPSIZE,
+            layers: HashMap::default(),
+            prefabs: Vec::new(),
+        }
+    }
+}
+impl SaveFile {
+    /// Replaces the tiles in the area of `prefab` at `pos` with the tiles of the prefab.
+    /// Only layers that exist in the prefab are changed
+    pub fn apply_prefab(&mut self, prefab: &Prefab, pos: UVec2) {
+        let area = URect::from_corners(pos, pos + prefab.size.max(UVec2::ONE) - UVec2::ONE);
+        for (layer_type, prefab_layer) in &prefab.layers {
+            let layer = self
+                .layers
+                .entry(*layer_type)
+                .or_insert(Layer { tiles: Vec::new() });
+            layer.tiles.retain(|tile| !area.contains(tile.pos));
+            layer
+                .tiles
+                .extend(prefab_layer.tiles.iter().map(|tile| Tile {
+                    pos: tile.pos + pos,
+                    ..tile.clone()
+                }));
+        }
+    }
+}
+/// A group of tiles that can be placed in the editor like a single tile.
+/// Stored as `.prefab.ron` in `assets/prefabs/`
+#[derive(Asset, TypePath, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct Prefab {
+    /// Shown in the prefab panel of the editor. Falls back to the file name
+    #[serde(default)]
+    pub name: String,
+    /// Width and height in tiles. Tile positions are relative to the bottom left corner
+    pub size: UVec2,
+    pub layers: HashMap<LayerType, Layer>,
+}
+/// Placement of a [`Prefab`] in a level
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct PrefabInstance {
+    /// Asset path of the prefab
+    pub path: String,
+    /// Position of the bottom left corner
+    pub pos: UVec2,
+    /// Width and height of the prefab when it was placed or the level was loaded
+    #[serde(default)]
+    pub size: UVec2,
+    /// Linked instances are replaced with the current version of the prefab whenever the level is loaded,
+    /// so changes to the prefab file show up in every level that uses it
+    #[serde(default)]
+    pub linked: bool,
+}
+impl PrefabInstance {
+    /// Tile positions covered by the prefab
+    pub fn area(&self) -> URect {
+        URect::from_corners(self.pos, self.pos + self.size.max(UVec2::ONE) - UVec2::ONE)
+    }
+}
+/// Information about a level that is not part of the tiles
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct LevelMeta {
+    /// Display name shown when the level starts
+    pub name: String,
+    pub author: String,
+    /// Completion time in seconds a player should aim for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub par_time: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub par_deaths: Option<usize>,
+    /// Asset path of the background track. Falls back to the main track
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub music: Option<String>,
+    /// Seconds a lit tower keeps burning. Falls back to [`crate::movement::ACTIVE_TIME`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tower_countdown: Option<f32>,
+    /// Level that is loaded once the portal is entered.
+    /// Same naming as in [`crate::editor::EditorEvents::LoadLevel`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+/// Only used to peek at the version before deserializing the whole level.
+#[derive(serde::Deserialize)]
+struct VersionHeader {
+    #[serde(default)]
+    version: u32,
+}
+/// Parses a level of any known version and migrates it to [`SAVE_FILE_VERSION`].
+pub fn parse_save_file(bytes: &[u8]) -> Result<SaveFile, SaveFileAssetLoaderError> {
+    let header = ron::de::from_bytes::<VersionHeader>(bytes)?;
+    if header.version > SAVE_FILE_VERSION {
+        return Err(SaveFileAssetLoaderError::UnsupportedVersion {
+            found: header.version,
+            supported: SAVE_FILE_VERSION,
+        });
+    }
+    let mut file = migrations::migrate(bytes, header.version)?;
+    if header.version < SAVE_FILE_VERSION {
+        info!(
+            "migrated level from version {} to {SAVE_FILE_VERSION}",
+            header.version
+        );
+    }
+    file.version = SAVE_FILE_VERSION;
+    Ok(file)
 }
 
 #[derive(Default)]
@@ -64,6 +300,10 @@ pub enum SaveFileAssetLoaderError {
     Io(#[from] std::io::Error),
     #[error("Could not parse RON: {0}")]
     RonSpannedError(#[from] ron::error::SpannedError),
+    #[error(
+        "Level has format version {found} but this build only supports up to version {supported}"
+    )]
+    UnsupportedVersion { found: u32, supported: u32 },
 }
 impl AssetLoader for SaveFileAssetLoader {
     type Asset = SaveFile;
@@ -73,26 +313,278 @@ impl AssetLoader for SaveFileAssetLoader {
         &self,
         reader: &mut dyn Reader,
         _settings: &(),
-        _load_context: &mut LoadContext<'_>,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let custom_asset = ron::de::from_bytes::<SaveFile>(&bytes)?;
-        Ok(custom_asset)
+        let mut file = parse_save_file(&bytes)?;
+        let mut prefabs = std::mem::take(&mut file.prefabs);
+        for instance in &mut prefabs {
+            if !instance.linked {
+                continue;
+            }
+            // loading it as a dependency reloads the level when the prefab changes
+            let prefab = match load_context
+                .loader()
+                .immediate()
+                .load::<Prefab>(&instance.path)
+                .await
+            {
+                Ok(prefab) => prefab,
+                Err(err) => {
+                    // the tiles saved with the level are kept
+                    warn!(
+                        "Could not load prefab {} of {}: {err}",
+                        instance.path,
+                        load_context.path().display()
+                    );
+                    continue;
+                }
+            };
+            file.apply_prefab(prefab.get(), instance.pos);
+            instance.size = prefab.get().size;
+        }
+        file.prefabs = prefabs;
+        Ok(file)
     }
 
     fn extensions(&self) -> &[&str] {
         &["ron"]
     }
 }
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Default)]
+pub struct CampaignAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum CampaignAssetLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+}
+impl AssetLoader for CampaignAssetLoader {
+    type Asset = Campaign;
+    type Settings = ();
+    type Error = CampaignAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes::<Campaign>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["campaign.ron"]
+    }
+}
+#[derive(Default)]
+pub struct LevelIndexAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelIndexAssetLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+}
+impl AssetLoader for LevelIndexAssetLoader {
+    type Asset = LevelIndex;
+    type Settings = ();
+    type Error = LevelIndexAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes::<LevelIndex>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["index.ron"]
+    }
+}
+#[derive(Default)]
+pub struct PrefabAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum PrefabAssetLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+}
+impl AssetLoader for PrefabAssetLoader {
+    type Asset = Prefab;
+    type Settings = ();
+    type Error = PrefabAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes::<Prefab>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["prefab.ron"]
+    }
+}
+#[derive(Default)]
+pub struct TilesetAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum TilesetAssetLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+}
+impl AssetLoader for TilesetAssetLoader {
+    type Asset = Tileset;
+    type Settings = ();
+    type Error = TilesetAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let def = ron::de::from_bytes::<TilesetDef>(&bytes)?;
+        let layout = TextureAtlasLayout::from_grid(
+            UVec2::splat(TILESIZE as u32),
+            def.columns,
+            def.rows,
+            None,
+            None,
+        );
+        Ok(Tileset {
+            image: load_context.load(def.image),
+            layout: load_context.add_labeled_asset("layout".into(), layout),
+            rules: def.rules,
+            autotile: def.autotile,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tileset.ron"]
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct Layer {
     pub tiles: Vec<Tile>,
 }
-#[derive(serde::Serialize, serde::Deserialize, Clone, Copy)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
 pub struct Tile {
     /// grid position
     pub pos: UVec2,
     /// texture atlas index
     pub index: usize,
+    /// Parameters for the spawned entity, for example the speed of a single enemy
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, TileProperty>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub enum TileProperty {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_unversioned_level() {
+        let file = parse_save_file(include_bytes!("../tests/fixtures/level1.v0.ron")).unwrap();
+        assert_eq!(file.version, SAVE_FILE_VERSION);
+        assert_eq!(file.size, TILEMAP_MAPSIZE);
+        assert!(file.meta.name.is_empty());
+        assert!(file.prefabs.is_empty());
+        let tiles: Vec<&Tile> = file
+            .layers
+            .values()
+            .flat_map(|layer| &layer.tiles)
+            .collect();
+        assert_eq!(tiles.len(), 240);
+        assert!(tiles.iter().all(|tile| tile.properties.is_empty()));
+        let tile = &file.layers[&LayerType::Fg].tiles[0];
+        assert_eq!((tile.pos, tile.index), (UVec2::new(14, 8), 12));
+    }
+    #[test]
+    fn migrates_version_3_level() {
+        let level = br#"(version: 3, meta: (name: "Cave", author: "Me", next: Some("level2")), size: (30, 12), layers: {
+            Entities: (tiles: [(pos: (2, 3), index: 4)]),
+        })"#;
+        let file = parse_save_file(level).unwrap();
+        assert_eq!(file.version, SAVE_FILE_VERSION);
+        assert_eq!(file.meta.name, "Cave");
+        assert_eq!(file.meta.next.as_deref(), Some("level2"));
+        assert_eq!(file.size, UVec2::new(30, 12));
+        let tile = &file.layers[&LayerType::Entities].tiles[0];
+        assert_eq!((tile.pos, tile.index), (UVec2::new(2, 3), 4));
+        assert!(tile.properties.is_empty());
+    }
+    #[test]
+    fn migrates_version_4_level() {
+        let level =
+            br#"(version: 4, meta: (name: "Cave", par_time: Some(30.)), size: (30, 12), layers: {
+            Entities: (tiles: [(pos: (2, 3), index: 4, properties: {"speed": Float(1500.)})]),
+        })"#;
+        let file = parse_save_file(level).unwrap();
+        assert_eq!(file.version, SAVE_FILE_VERSION);
+        assert_eq!(file.meta.name, "Cave");
+        assert_eq!(file.meta.par_time, Some(30.));
+        assert!(file.prefabs.is_empty());
+        let tile = &file.layers[&LayerType::Entities].tiles[0];
+        assert_eq!(tile.properties["speed"], TileProperty::Float(1500.));
+    }
+    #[test]
+    fn reads_written_level() {
+        let mut file = SaveFile::default();
+        let mut tile = Tile {
+            pos: UVec2::new(1, 2),
+            index: 3,
+            properties: default(),
+        };
+        tile.properties
+            .insert("speed".into(), TileProperty::Float(1500.));
+        file.layers
+            .insert(LayerType::Entities, Layer { tiles: vec![tile] });
+        let text = save_file_to_string(&file).unwrap();
+        let file = parse_save_file(text.as_bytes()).unwrap();
+        let tile = &file.layers[&LayerType::Entities].tiles[0];
+        assert_eq!(tile.properties["speed"], TileProperty::Float(1500.));
+    }
+    #[test]
+    fn rejects_future_version() {
+        let result = parse_save_file(b"(version: 99, layers: {})");
+        assert!(matches!(
+            result,
+            Err(SaveFileAssetLoaderError::UnsupportedVersion { found: 99, .. })
+        ));
+    }
+    #[test]
+    fn merges_bundled_levels_into_stored_index() {
+        let mut stored = LevelIndex {
+            levels: vec!["level2".into(), "my_level".into()],
+        };
+        stored.merge(LevelIndex {
+            levels: vec!["level1".into(), "level2".into(), "level3".into()],
+        });
+        assert_eq!(stored.levels, ["level2", "my_level", "level1", "level3"]);
+    }
 }
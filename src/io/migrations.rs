@@ -0,0 +1,192 @@
+//! Older versions of the level format.
+//!
+//! Every version keeps the struct it was written with, so a migration can rename, remove or change fields.
+//! A level is deserialized into the struct of its version and upgraded one version at a time.
+//! When the format changes, copy the current [`SaveFile`] here as the struct of the old version
+//! and add a `migrate` that turns it into the new one.
+//! Only the last migration converts into the current types, the structs of older versions never use them.
+use std::collections::{BTreeMap, HashMap};
+
+use bevy::prelude::*;
+use ron::error::SpannedError;
+
+use super::{Layer, LevelMeta, SaveFile, Tile, TileProperty};
+use crate::map::{LayerType, TILEMAP_MAPSIZE};
+
+/// Deserializes a level of `version` and upgrades it to [`super::SAVE_FILE_VERSION`]
+pub(super) fn migrate(bytes: &[u8], version: u32) -> Result<SaveFile, SpannedError> {
+    use ron::de::from_bytes;
+    Ok(match version {
+        0 | 1 => from_bytes::<SaveFileV1>(bytes)?
+            .migrate()
+            .migrate()
+            .migrate()
+            .migrate(),
+        2 => from_bytes::<SaveFileV2>(bytes)?
+            .migrate()
+            .migrate()
+            .migrate(),
+        3 => from_bytes::<SaveFileV3>(bytes)?.migrate().migrate(),
+        4 => from_bytes::<SaveFileV4>(bytes)?.migrate(),
+        _ => from_bytes::<SaveFile>(bytes)?,
+    })
+}
+/// Version 1 only introduced the `version` header itself, version 0 files have none
+#[derive(serde::Deserialize)]
+struct SaveFileV1 {
+    layers: HashMap<LayerType, LayerV1>,
+}
+#[derive(serde::Deserialize)]
+struct LayerV1 {
+    tiles: Vec<TileV1>,
+}
+#[derive(serde::Deserialize)]
+struct TileV1 {
+    pos: UVec2,
+    index: usize,
+}
+impl SaveFileV1 {
+    /// Older levels simply have no metadata
+    fn migrate(self) -> SaveFileV2 {
+        SaveFileV2 {
+            meta: LevelMetaV2::default(),
+            layers: self.layers,
+        }
+    }
+}
+/// Version 2 added [`LevelMeta`]
+#[derive(serde::Deserialize)]
+struct SaveFileV2 {
+    meta: LevelMetaV2,
+    layers: HashMap<LayerType, LayerV1>,
+}
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct LevelMetaV2 {
+    name: String,
+    author: String,
+    par_time: Option<f32>,
+    par_deaths: Option<usize>,
+    music: Option<String>,
+    tower_countdown: Option<f32>,
+    next: Option<String>,
+}
+impl SaveFileV2 {
+    /// Older levels were all made for [`TILEMAP_MAPSIZE`]
+    fn migrate(self) -> SaveFileV3 {
+        SaveFileV3 {
+            meta: self.meta,
+            size: TILEMAP_MAPSIZE,
+            layers: self.layers,
+        }
+    }
+}
+/// Version 3 added the map size
+#[derive(serde::Deserialize)]
+struct SaveFileV3 {
+    meta: LevelMetaV2,
+    size: UVec2,
+    layers: HashMap<LayerType, LayerV1>,
+}
+impl SaveFileV3 {
+    /// Older tiles have no properties
+    fn migrate(self) -> SaveFileV4 {
+        let layers = self
+            .layers
+            .into_iter()
+            .map(|(layer_type, layer)| {
+                let tiles = layer
+                    .tiles
+                    .into_iter()
+                    .map(|tile| TileV4 {
+                        pos: tile.pos,
+                        index: tile.index,
+                        properties: BTreeMap::new(),
+                    })
+                    .collect();
+                (layer_type, LayerV4 { tiles })
+            })
+            .collect();
+        SaveFileV4 {
+            meta: self.meta,
+            size: self.size,
+            layers,
+        }
+    }
+}
+/// Version 4 added [`Tile::properties`]
+#[derive(serde::Deserialize)]
+struct SaveFileV4 {
+    meta: LevelMetaV2,
+    size: UVec2,
+    layers: HashMap<LayerType, LayerV4>,
+}
+#[derive(serde::Deserialize)]
+struct LayerV4 {
+    tiles: Vec<TileV4>,
+}
+#[derive(serde::Deserialize)]
+struct TileV4 {
+    pos: UVec2,
+    index: usize,
+    #[serde(default)]
+    properties: BTreeMap<String, TilePropertyV4>,
+}
+#[derive(serde::Deserialize)]
+enum TilePropertyV4 {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+impl SaveFileV4 {
+    /// Older levels have no prefab instances
+    fn migrate(self) -> SaveFile {
+        let meta = self.meta;
+        let layers = self
+            .layers
+            .into_iter()
+            .map(|(layer_type, layer)| {
+                let tiles = layer
+                    .tiles
+                    .into_iter()
+                    .map(|tile| Tile {
+                        pos: tile.pos,
+                        index: tile.index,
+                        properties: tile
+                            .properties
+                            .into_iter()
+                            .map(|(key, property)| (key, property.migrate()))
+                            .collect(),
+                    })
+                    .collect();
+                (layer_type, Layer { tiles })
+            })
+            .collect();
+        SaveFile {
+            version: super::SAVE_FILE_VERSION,
+            meta: LevelMeta {
+                name: meta.name,
+                author: meta.author,
+                par_time: meta.par_time,
+                par_deaths: meta.par_deaths,
+                music: meta.music,
+                tower_countdown: meta.tower_countdown,
+                next: meta.next,
+            },
+            size: self.size,
+            layers,
+            prefabs: Vec::new(),
+        }
+    }
+}
+impl TilePropertyV4 {
+    fn migrate(self) -> TileProperty {
+        match self {
+            TilePropertyV4::Bool(value) => TileProperty::Bool(value),
+            TilePropertyV4::Int(value) => TileProperty::Int(value),
+            TilePropertyV4::Float(value) => TileProperty::Float(value),
+            TilePropertyV4::String(value) => TileProperty::String(value),
+        }
+    }
+}
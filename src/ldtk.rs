@@ -0,0 +1,383 @@
+//! Import of [LDtk](https://ldtk.io/) projects.
+//!
+//! Every level of a project becomes a [`SaveFile`]. The project itself loads as its first level,
+//! the others are labeled with their identifier, so `world.ldtk#Level_1` is the level `Level_1` of `world.ldtk`.
+//! Levels are played in the order of the project.
+//!
+//! Tile, auto and IntGrid layers named `Bg` or `Fg` fill the layer with the same name.
+//! Tile ids are used as atlas index in the tileset of that layer.
+//! IntGrid values without tiles are named after an [`OnSpawnTrigger`] and become the first tile with that trigger.
+//! Entities are named after an [`OnSpawnTrigger`] as well and are placed in the layer whose tileset has a rule for them.
+//! Their custom fields are stored in [`Tile::properties`].
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{
+    Deserialize,
+    de::value::{Error as ValueError, StrDeserializer},
+};
+use thiserror::Error;
+
+use crate::{
+    entity::{OnSpawnTrigger, Rule},
+    io::{self, Layer, SaveFile, Tile, TileProperty},
+    map::{LayerType, Tileset},
+};
+
+#[derive(Default)]
+pub struct LdtkProjectAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum LdtkProjectAssetLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse LDtk project: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Could not load tileset: {0}")]
+    Tileset(#[from] bevy::asset::LoadDirectError),
+    #[error("Unsupported LDtk project: {0}")]
+    Unsupported(String),
+}
+impl AssetLoader for LdtkProjectAssetLoader {
+    type Asset = SaveFile;
+    type Settings = ();
+    type Error = LdtkProjectAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let project = serde_json::from_slice::<LdtkProject>(&bytes)?;
+        if project.external_levels {
+            return Err(LdtkProjectAssetLoaderError::Unsupported(
+                "levels saved in separate files can not be imported".into(),
+            ));
+        }
+        let mut rules = HashMap::new();
+        for layer_type in (0..3).map(LayerType::from_u8) {
+            let tileset = load_context
+                .loader()
+                .immediate()
+                .load::<Tileset>(layer_type.tileset_path())
+                .await?;
+            rules.insert(layer_type, tileset.get().rules.clone());
+        }
+        let int_grid_values: HashMap<i64, &Vec<LdtkIntGridValue>> = project
+            .defs
+            .layers
+            .iter()
+            .map(|layer| (layer.uid, &layer.int_grid_values))
+            .collect();
+        let project_name = io::level_name(load_context.path());
+        let levels: Vec<&LdtkLevel> = project
+            .levels
+            .iter()
+            .chain(project.worlds.iter().flat_map(|world| &world.levels))
+            .collect();
+        let mut first = None;
+        for (i, level) in levels.iter().enumerate() {
+            let mut file = level.to_save_file(&rules, &int_grid_values)?;
+            file.meta.name = level.identifier.clone();
+            if let (Some(project_name), Some(next)) = (&project_name, levels.get(i + 1)) {
+                file.meta.next = Some(format!("{project_name}#{}", next.identifier));
+            }
+            if first.is_none() {
+                first = Some(file.clone());
+            }
+            load_context.add_labeled_asset(level.identifier.clone(), file);
+        }
+        first
+            .ok_or_else(|| LdtkProjectAssetLoaderError::Unsupported("project has no levels".into()))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkProject {
+    #[serde(default)]
+    external_levels: bool,
+    defs: LdtkDefs,
+    #[serde(default)]
+    levels: Vec<LdtkLevel>,
+    /// Only used by projects with multiple worlds
+    #[serde(default)]
+    worlds: Vec<LdtkWorld>,
+}
+#[derive(Deserialize)]
+struct LdtkWorld {
+    levels: Vec<LdtkLevel>,
+}
+#[derive(Deserialize)]
+struct LdtkDefs {
+    layers: Vec<LdtkLayerDef>,
+}
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLayerDef {
+    uid: i64,
+    #[serde(default)]
+    int_grid_values: Vec<LdtkIntGridValue>,
+}
+#[derive(Deserialize)]
+struct LdtkIntGridValue {
+    value: i64,
+    identifier: Option<String>,
+}
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLevel {
+    identifier: String,
+    layer_instances: Option<Vec<LdtkLayer>>,
+}
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLayer {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__cWid")]
+    width: u32,
+    #[serde(rename = "__cHei")]
+    height: u32,
+    #[serde(rename = "__gridSize")]
+    grid_size: u32,
+    layer_def_uid: i64,
+    #[serde(default)]
+    int_grid_csv: Vec<i64>,
+    #[serde(default)]
+    grid_tiles: Vec<LdtkTile>,
+    #[serde(default)]
+    auto_layer_tiles: Vec<LdtkTile>,
+    #[serde(default)]
+    entity_instances: Vec<LdtkEntity>,
+}
+#[derive(Deserialize)]
+struct LdtkTile {
+    /// Position in pixel from the top left
+    px: [u32; 2],
+    t: usize,
+}
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkEntity {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__grid")]
+    grid: [u32; 2],
+    #[serde(default)]
+    field_instances: Vec<LdtkField>,
+}
+#[derive(Deserialize)]
+struct LdtkField {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__value")]
+    value: serde_json::Value,
+}
+impl LdtkLevel {
+    fn to_save_file(
+        &self,
+        rules: &HashMap<LayerType, Vec<Rule>>,
+        int_grid_values: &HashMap<i64, &Vec<LdtkIntGridValue>>,
+    ) -> Result<SaveFile, LdtkProjectAssetLoaderError> {
+        let layers = self.layer_instances.as_deref().unwrap_or_default();
+        let mut file = SaveFile::default();
+        if let Some(layer) = layers.first() {
+            file.size = UVec2::new(layer.width, layer.height);
+        }
+        // LDtk counts rows from the top
+        let pos = |layer: &LdtkLayer, [x, y]: [u32; 2]| {
+            if x >= layer.width || y >= layer.height {
+                return Err(LdtkProjectAssetLoaderError::Unsupported(format!(
+                    "({x}, {y}) is outside of layer {}",
+                    layer.identifier
+                )));
+            }
+            Ok(UVec2::new(x, layer.height - 1 - y))
+        };
+        for layer in layers {
+            match layer.identifier.as_str() {
+                "Bg" | "Fg" => {
+                    if layer.grid_size == 0 {
+                        return Err(LdtkProjectAssetLoaderError::Unsupported(format!(
+                            "layer {} has a grid size of 0",
+                            layer.identifier
+                        )));
+                    }
+                    let layer_type = if layer.identifier == "Bg" {
+                        LayerType::Bg
+                    } else {
+                        LayerType::Fg
+                    };
+                    let tiles = &mut file
+                        .layers
+                        .entry(layer_type)
+                        .or_insert(Layer { tiles: Vec::new() })
+                        .tiles;
+                    for tile in layer.grid_tiles.iter().chain(&layer.auto_layer_tiles) {
+                        tiles.push(Tile {
+                            pos: pos(layer, tile.px.map(|px| px / layer.grid_size))?,
+                            index: tile.t,
+                            ..default()
+                        });
+                    }
+                    if !layer.auto_layer_tiles.is_empty() {
+                        continue;
+                    }
+                    let values = int_grid_values.get(&layer.layer_def_uid);
+                    for (i, value) in layer.int_grid_csv.iter().enumerate() {
+                        let Some(trigger) = values
+                            .and_then(|values| values.iter().find(|v| v.value == *value))
+                            .and_then(|value| parse_trigger(value.identifier.as_deref()?))
+                        else {
+                            continue;
+                        };
+                        let Some(rule) = rules[&layer_type].iter().find(|r| r.on_spawn == trigger)
+                        else {
+                            warn!("no tile in layer {} spawns {trigger:?}", layer.identifier);
+                            continue;
+                        };
+                        let width = layer.width.max(1);
+                        let grid = [i as u32 % width, i as u32 / width];
+                        tiles.push(Tile {
+                            pos: pos(layer, grid)?,
+                            index: rule.target_index,
+                            ..default()
+                        });
+                    }
+                }
+                _ => {
+                    for entity in &layer.entity_instances {
+                        let Some(trigger) = parse_trigger(&entity.identifier) else {
+                            info!("ignoring LDtk entity {}", entity.identifier);
+                            continue;
+                        };
+                        let target = [LayerType::Entities, LayerType::Fg, LayerType::Bg]
+                            .into_iter()
+                            .find_map(|layer_type| {
+                                let rule =
+                                    rules[&layer_type].iter().find(|r| r.on_spawn == trigger)?;
+                                Some((layer_type, rule.target_index))
+                            });
+                        let Some((layer_type, index)) = target else {
+                            warn!("no tileset has a tile that spawns {trigger:?}");
+                            continue;
+                        };
+                        let properties = entity
+                            .field_instances
+                            .iter()
+                            .filter_map(|field| {
+                                Some((field.identifier.clone(), to_property(&field.value)?))
+                            })
+                            .collect();
+                        file.layers
+                            .entry(layer_type)
+                            .or_insert(Layer { tiles: Vec::new() })
+                            .tiles
+                            .push(Tile {
+                                pos: pos(layer, entity.grid)?,
+                                index,
+                                properties,
+                            });
+                    }
+                }
+            }
+        }
+        Ok(file)
+    }
+}
+fn parse_trigger(name: &str) -> Option<OnSpawnTrigger> {
+    OnSpawnTrigger::deserialize(StrDeserializer::<ValueError>::new(name)).ok()
+}
+/// Fields without a value or with a type that has no [`TileProperty`] are skipped
+fn to_property(value: &serde_json::Value) -> Option<TileProperty> {
+    match value {
+        serde_json::Value::Bool(value) => Some(TileProperty::Bool(*value)),
+        serde_json::Value::Number(number) => Some(match number.as_i64() {
+            Some(value) => TileProperty::Int(value),
+            None => TileProperty::Float(number.as_f64()?),
+        }),
+        serde_json::Value::String(value) => Some(TileProperty::String(value.clone())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> HashMap<LayerType, Vec<Rule>> {
+        HashMap::from([
+            (LayerType::Bg, Vec::new()),
+            (
+                LayerType::Fg,
+                ron::from_str("[(index: 7, trigger: Collider)]").unwrap(),
+            ),
+            (
+                LayerType::Entities,
+                ron::from_str("[(index: 2, trigger: Enemy)]").unwrap(),
+            ),
+        ])
+    }
+    fn level(layers: &str) -> LdtkLevel {
+        let level = format!(r#"{{"identifier": "Level_0", "layerInstances": [{layers}]}}"#);
+        serde_json::from_str(&level).unwrap()
+    }
+    #[test]
+    fn counts_rows_from_the_bottom() {
+        let level = level(
+            r#"{"__identifier": "Fg", "__cWid": 4, "__cHei": 3, "__gridSize": 16, "layerDefUid": 1,
+                "gridTiles": [{"px": [16, 0], "t": 5}]},
+               {"__identifier": "Entities", "__cWid": 4, "__cHei": 3, "__gridSize": 16, "layerDefUid": 2,
+                "entityInstances": [{"__identifier": "Enemy", "__grid": [3, 2],
+                    "fieldInstances": [{"__identifier": "speed", "__value": 1500}]}]}"#,
+        );
+        let file = level.to_save_file(&rules(), &HashMap::new()).unwrap();
+        assert_eq!(file.size, UVec2::new(4, 3));
+        let tile = &file.layers[&LayerType::Fg].tiles[0];
+        assert_eq!((tile.pos, tile.index), (UVec2::new(1, 2), 5));
+        let enemy = &file.layers[&LayerType::Entities].tiles[0];
+        assert_eq!((enemy.pos, enemy.index), (UVec2::new(3, 0), 2));
+        assert_eq!(enemy.properties["speed"], TileProperty::Int(1500));
+    }
+    #[test]
+    fn maps_int_grid_values_to_rules() {
+        let level = level(
+            r#"{"__identifier": "Fg", "__cWid": 2, "__cHei": 2, "__gridSize": 16, "layerDefUid": 1,
+                "intGridCsv": [0, 0, 1, 0]}"#,
+        );
+        let values = vec![LdtkIntGridValue {
+            value: 1,
+            identifier: Some("Collider".into()),
+        }];
+        let file = level
+            .to_save_file(&rules(), &HashMap::from([(1, &values)]))
+            .unwrap();
+        let tile = &file.layers[&LayerType::Fg].tiles[0];
+        assert_eq!((tile.pos, tile.index), (UVec2::new(0, 0), 7));
+    }
+    #[test]
+    fn rejects_entities_outside_of_the_layer() {
+        let level = level(
+            r#"{"__identifier": "Entities", "__cWid": 4, "__cHei": 3, "__gridSize": 16, "layerDefUid": 2,
+                "entityInstances": [{"__identifier": "Enemy", "__grid": [0, 3]}]}"#,
+        );
+        assert!(level.to_save_file(&rules(), &HashMap::new()).is_err());
+    }
+    #[test]
+    fn rejects_grid_size_of_0() {
+        let level = level(
+            r#"{"__identifier": "Bg", "__cWid": 4, "__cHei": 3, "__gridSize": 0, "layerDefUid": 1,
+                "gridTiles": [{"px": [16, 0], "t": 5}]}"#,
+        );
+        assert!(level.to_save_file(&rules(), &HashMap::new()).is_err());
+    }
+}
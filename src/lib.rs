@@ -0,0 +1,77 @@
+mod animation;
+mod aseprite;
+mod asset_loading;
+mod audio;
+pub mod autotile;
+mod campaign;
+mod combat;
+mod editor;
+pub mod entity;
+pub mod io;
+mod ldtk;
+pub mod map;
+mod movement;
+mod screens;
+mod storage;
+pub mod thumbnail;
+mod tiled;
+mod utils;
+pub mod validate;
+mod widget;
+use avian2d::prelude::RigidBody;
+use bevy::asset::AssetMetaCheck;
+use bevy::prelude::*;
+use screens::GameState;
+
+use crate::map::BACKGROUND_COLOR;
+
+pub fn app_plugin(app: &mut App) {
+    app.add_plugins((
+        storage::plugin,
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Window {
+                    title: "Hnoss".to_string(),
+                    fit_canvas_to_parent: true,
+                    ..default()
+                }
+                .into(),
+                ..default()
+            })
+            .set(AssetPlugin {
+                meta_check: AssetMetaCheck::Never,
+                ..default()
+            })
+            .set(ImagePlugin::default_nearest()),
+        screens::plugin,
+        combat::plugin,
+        movement::plugin,
+        animation::plugin,
+        asset_loading::plugin,
+        audio::plugin,
+        map::plugin,
+        editor::plugin,
+        campaign::plugin,
+    ))
+    .add_systems(Startup, (init_camera, init_gizmo));
+}
+fn init_camera(mut commands: Commands) {
+    let mut projection = OrthographicProjection::default_2d();
+    projection.scale = 1. / 4.;
+    commands.spawn((
+        Camera2d,
+        Camera {
+            clear_color: ClearColorConfig::Custom(BACKGROUND_COLOR),
+            ..default()
+        },
+        Projection::Orthographic(projection.clone()),
+        MainCamera,
+        RigidBody::Kinematic,
+    ));
+}
+fn init_gizmo(mut config_store: ResMut<GizmoConfigStore>) {
+    let (config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
+    config.line.width += 2.;
+}
+#[derive(Component)]
+struct MainCamera;
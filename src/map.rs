@@ -1,15 +1,18 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use crate::{
     MainCamera,
+    animation::AnimationSet,
     asset_loading::LoadResource,
-    editor::{EditorEvents, RemoveOnLevelSwap, spawn_tiled},
-    entity::{self, OnSpawnTrigger, Player, Portal, Rule, TowerCountdown},
+    autotile::AutotileGroup,
+    campaign::{Campaign, CampaignProgress},
+    editor::{EditorEvents, EditorMeta, RemoveOnLevelSwap, UiRespawnTrigger, spawn_tiled},
+    entity::{self, Player, Portal, Rule, TileProperties, TowerCountdown},
     io::{self, SaveFile, Tile},
-    map,
-    movement::DASH_RADIUS,
+    ldtk, map,
+    movement::{ACTIVE_TIME, DASH_RADIUS},
     screens::GameState,
-    utils,
+    tiled, utils,
 };
 use bevy::{
     image::{ImageLoaderSettings, ImageSampler},
@@ -28,37 +31,69 @@ use bevy_ecs_tilemap::{
 pub const TILESIZE: i32 = 16;
 pub const PLAYERSIZE: UVec2 = UVec2::new(18, 26);
 pub const ENEMYSIZE: UVec2 = UVec2::new(18, 26);
-pub const MAIN_TEXTURE_PATH: &str = "textures.png";
-pub const ENTITY_TEXTURE_PATH: &str = "entities.png";
+pub const TERRAIN_TILESET_PATH: &str = "tilesets/terrain.tileset.ron";
+pub const ENTITY_TILESET_PATH: &str = "tilesets/entities.tileset.ron";
 pub const FIRE_TEXTURE_PATH: &str = "fire.png";
 pub const PLAYER_TEXTURE_PATH: &str = "char.png";
 pub const PLAYER_ACTIVE_TEXTURE_PATH: &str = "char_active.png";
 pub const ENEMIES_TEXTURE_PATH: &str = "enemies.png";
 pub const KEYS_TEXTURE: &str = "keys.png";
+pub const PLAYER_ANIMATIONS_PATH: &str = "animations/player.anim.ron";
+pub const ENEMY_ANIMATIONS_PATH: &str = "animations/enemy.anim.ron";
+pub const FIRE_ANIMATIONS_PATH: &str = "animations/fire.anim.ron";
 const MAIN_MENU_IMAGE: &str = "goblin_splash.jpg";
 pub const TILEMAP_OFFSET: Vec2 = Vec2::new(-100., -100.);
 pub const TILEMAP_ANCHOR: TilemapAnchor = TilemapAnchor::BottomLeft;
 pub const BACKGROUND_COLOR: Color = Color::srgba_u8(41, 41, 41, 255);
 pub const DEBUG_BACKGROUND_COLOR: Color = Color::srgba_u8(81, 81, 81, 255);
 
+/// Map size of levels that do not specify one
 pub const TILEMAP_MAPSIZE: UVec2 = UVec2::new(44, 20);
 pub fn plugin(app: &mut App) {
-    app.init_resource::<Textures>()
+    app.init_asset_loader::<io::TilesetAssetLoader>()
+        .init_asset::<Tileset>()
+        .init_resource::<Textures>()
+        .init_resource::<MapSize>()
         .add_plugins(bevy_ecs_tilemap::TilemapPlugin)
         .add_plugins(entity::plugin)
         .init_asset_loader::<io::SaveFileAssetLoader>()
+        .init_asset_loader::<tiled::TiledMapAssetLoader>()
+        .init_asset_loader::<ldtk::LdtkProjectAssetLoader>()
         .init_asset::<io::SaveFile>()
+        .init_asset_loader::<io::CampaignAssetLoader>()
+        .init_asset::<Campaign>()
+        .init_asset_loader::<io::LevelIndexAssetLoader>()
+        .init_asset::<io::LevelIndex>()
+        .init_asset_loader::<io::PrefabAssetLoader>()
+        .init_asset::<io::Prefab>()
         .init_resource::<MousePosition>()
         .add_systems(
             Update,
-            (update_mouse_position, load_level, check_portal_activation),
+            (
+                update_mouse_position,
+                apply_tilesets,
+                load_level,
+                check_portal_activation,
+            )
+                .chain(),
         )
         .add_systems(OnEnter(GameState::Running), init_map)
         .load_resource::<Textures>();
 }
+/// Width and height in tiles of the currently loaded level
+#[derive(Resource, Clone, Copy)]
+pub struct MapSize(pub UVec2);
+impl Default for MapSize {
+    fn default() -> Self {
+        MapSize(TILEMAP_MAPSIZE)
+    }
+}
 fn init_map(
     mut commands: Commands,
     textures: Res<map::Textures>,
+    map_size: Res<MapSize>,
+    progress: Res<CampaignProgress>,
+    campaigns: Res<Assets<Campaign>>,
     mut event_writer: EventWriter<EditorEvents>,
 ) {
     let tile_size = TilemapTileSize {
@@ -68,10 +103,7 @@ fn init_map(
     let grid_size = tile_size.into();
 
     let map_type = TilemapType::default();
-    let map_size = TilemapSize {
-        x: TILEMAP_MAPSIZE.x,
-        y: TILEMAP_MAPSIZE.y,
-    };
+    let map_size: TilemapSize = map_size.0.into();
     for layer_type in (0..3).map(LayerType::from_u8) {
         commands
             .spawn(TilemapBundle {
@@ -88,7 +120,7 @@ fn init_map(
             .insert(layer_type);
     }
     event_writer.write(EditorEvents::LoadLevel {
-        name: Some("level0".into()),
+        name: Some(progress.first_level(&campaigns)),
     });
 }
 #[derive(
@@ -118,6 +150,12 @@ impl LayerType {
     pub fn next(&self) -> Self {
         LayerType::from_u8((*self as u8 + 1) % 3)
     }
+    pub const fn tileset_path(&self) -> &'static str {
+        match self {
+            LayerType::Bg | LayerType::Fg => TERRAIN_TILESET_PATH,
+            LayerType::Entities => ENTITY_TILESET_PATH,
+        }
+    }
     pub const fn z(&self) -> f32 {
         match self {
             LayerType::Bg => 0.,
@@ -141,95 +179,86 @@ pub struct TexturePack {
     pub texture: Handle<Image>,
     pub layout: Handle<TextureAtlasLayout>,
     pub rules: Vec<Rule>,
+    pub autotile: Vec<AutotileGroup>,
+}
+/// Image, grid and rules of the tiles that can be placed in a layer.
+/// Loaded from `*.tileset.ron` files
+#[derive(Asset, TypePath)]
+pub struct Tileset {
+    #[dependency]
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub rules: Vec<Rule>,
+    pub autotile: Vec<AutotileGroup>,
+}
+/// Format of `*.tileset.ron` files
+#[derive(serde::Deserialize)]
+pub struct TilesetDef {
+    /// Asset path of the image
+    pub image: String,
+    pub columns: u32,
+    pub rows: u32,
+    /// Tiles without a rule are only drawn
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    /// Brushes that pick the edge and corner variants of their tiles in the editor
+    #[serde(default)]
+    pub autotile: Vec<AutotileGroup>,
 }
 #[derive(Resource, Asset, TypePath)]
 pub struct Textures {
+    /// Filled from [`Textures::tilesets`] once they are loaded
     pub pack: HashMap<LayerType, TexturePack>,
+    /// Tileset of each layer, indexed by the [`LayerType`]
+    #[dependency]
+    pub tilesets: Vec<Handle<Tileset>>,
     pub player: TexturePack,
     pub player_active: TexturePack,
     pub enemy: TexturePack,
     pub fire: TexturePack,
+    /// Clips and grids of the sprite sheets above. The player textures share one set
+    #[dependency]
+    pub player_animations: Handle<AnimationSet>,
+    #[dependency]
+    pub enemy_animations: Handle<AnimationSet>,
+    #[dependency]
+    pub fire_animations: Handle<AnimationSet>,
     pub main_menu_image: Handle<Image>,
     pub keys: Handle<Image>,
 }
 impl FromWorld for Textures {
     fn from_world(world: &mut World) -> Self {
-        let mut texture_atlas_layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
-        let main_layout =
-            TextureAtlasLayout::from_grid(UVec2::splat(TILESIZE as u32), 4, 5, None, None);
-        let entity_layout =
-            TextureAtlasLayout::from_grid(UVec2::splat(TILESIZE as u32), 8, 4, None, None);
-        let fire_layout =
-            TextureAtlasLayout::from_grid(UVec2::splat(TILESIZE as u32), 8, 4, None, None);
-        let player_layout = TextureAtlasLayout::from_grid(PLAYERSIZE, 6, 4, None, None);
-        let enemy_layout = TextureAtlasLayout::from_grid(ENEMYSIZE, 6, 3, None, None);
-        let main_layout = texture_atlas_layouts.add(main_layout);
-        let entity_layout = texture_atlas_layouts.add(entity_layout);
-        let fire_layout = texture_atlas_layouts.add(fire_layout);
-        let player_layout = texture_atlas_layouts.add(player_layout);
-        let enemy_layout = texture_atlas_layouts.add(enemy_layout);
         let asset_server = world.resource::<AssetServer>();
-        let mut map = HashMap::new();
-        for layer in &[LayerType::Bg, LayerType::Fg, LayerType::Entities] {
-            match layer {
-                LayerType::Bg | LayerType::Fg => {
-                    let main_textures = asset_server.load(MAIN_TEXTURE_PATH);
-                    map.insert(
-                        *layer,
-                        TexturePack {
-                            texture: main_textures,
-                            layout: main_layout.clone(),
-                            rules: vec![
-                                Rule::new(5, OnSpawnTrigger::Collider, true),
-                                Rule::new(8, OnSpawnTrigger::Tower, true),
-                                Rule::new(9, OnSpawnTrigger::Pit, true),
-                                Rule::new(13, OnSpawnTrigger::Pit, true),
-                                Rule::new(16, OnSpawnTrigger::Pit, true),
-                                Rule::new(17, OnSpawnTrigger::Pit, true),
-                                Rule::new(18, OnSpawnTrigger::Pit, true),
-                                Rule::new(19, OnSpawnTrigger::Pit, true),
-                            ],
-                        },
-                    );
-                }
-                LayerType::Entities => {
-                    let texture = asset_server.load(ENTITY_TEXTURE_PATH);
-                    map.insert(
-                        *layer,
-                        TexturePack {
-                            texture,
-                            layout: entity_layout.clone(),
-                            rules: vec![
-                                Rule::new(0, OnSpawnTrigger::Player, false),
-                                Rule::new(1, OnSpawnTrigger::Enemy, false),
-                                Rule::new(2, OnSpawnTrigger::Portal, true),
-                                Rule::new(3, OnSpawnTrigger::PlayerSpawnPlatform, true),
-                                Rule::new(4, OnSpawnTrigger::GameFinishedPlatform, true),
-                            ],
-                        },
-                    );
-                }
-            }
-        }
+        // the grids are defined in the animation sets
+        let player_layout = asset_server.load(format!("{PLAYER_ANIMATIONS_PATH}#layout"));
+        let enemy_layout = asset_server.load(format!("{ENEMY_ANIMATIONS_PATH}#layout"));
+        let fire_layout = asset_server.load(format!("{FIRE_ANIMATIONS_PATH}#layout"));
+        let tilesets = (0..3)
+            .map(|layer| asset_server.load(LayerType::from_u8(layer).tileset_path()))
+            .collect();
         let player = TexturePack {
             texture: asset_server.load(PLAYER_TEXTURE_PATH),
             layout: player_layout.clone(),
             rules: Vec::default(),
+            autotile: Vec::default(),
         };
         let player_active = TexturePack {
             texture: asset_server.load(PLAYER_ACTIVE_TEXTURE_PATH),
             layout: player_layout,
             rules: Vec::default(),
+            autotile: Vec::default(),
         };
         let enemy = TexturePack {
             texture: asset_server.load(ENEMIES_TEXTURE_PATH),
             layout: enemy_layout,
             rules: Vec::default(),
+            autotile: Vec::default(),
         };
         let fire = TexturePack {
             texture: asset_server.load(FIRE_TEXTURE_PATH),
             layout: fire_layout,
             rules: Vec::default(),
+            autotile: Vec::default(),
         };
 
         let main_menu_image = asset_server.load_with_settings(
@@ -240,7 +269,11 @@ impl FromWorld for Textures {
         );
         let keys = asset_server.load(KEYS_TEXTURE);
         Textures {
-            pack: map,
+            pack: HashMap::new(),
+            tilesets,
+            player_animations: asset_server.load(PLAYER_ANIMATIONS_PATH),
+            enemy_animations: asset_server.load(ENEMY_ANIMATIONS_PATH),
+            fire_animations: asset_server.load(FIRE_ANIMATIONS_PATH),
             keys,
             player,
             enemy,
@@ -258,12 +291,13 @@ pub struct MousePosition {
     pub dash_point: Vec2,
 }
 impl MousePosition {
-    pub fn to_tilepos(&self) -> Option<TilePos> {
-        utils::world_to_tilepos(self.world_position, TILEMAP_OFFSET)
+    pub fn to_tilepos(&self, map_size: &MapSize) -> Option<TilePos> {
+        utils::world_to_tilepos(self.world_position, map_size.0, TILEMAP_OFFSET)
     }
-    pub fn to_tilepos_vec2(&self) -> Option<Vec2> {
-        self.to_tilepos()
-            .map(|tilepos| utils::tile_to_world(&tilepos, TILEMAP_OFFSET.extend(0.)).xy())
+    pub fn to_tilepos_vec2(&self, map_size: &MapSize) -> Option<Vec2> {
+        self.to_tilepos(map_size).map(|tilepos| {
+            utils::tile_to_world(&tilepos, map_size.0, TILEMAP_OFFSET.extend(0.)).xy()
+        })
     }
 }
 pub fn convert_to_tile_pos(position: Vec2) -> TilePos {
@@ -307,36 +341,103 @@ fn update_mouse_position(
     }
     mouse_position.world_position = position;
 }
+/// Keeps [`Textures::pack`] and the tilemaps in sync with the tilesets, also when they are hot reloaded.
+/// Tiles that are already spawned keep their old rules until the level is loaded again
+fn apply_tilesets(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<Tileset>>,
+    tilesets: Res<Assets<Tileset>>,
+    mut textures: ResMut<Textures>,
+    editor_meta: Res<EditorMeta>,
+    mut tilemaps: Query<(&mut TilemapTexture, &LayerType)>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        let Some(tileset) = tilesets.get(*id) else {
+            continue;
+        };
+        for layer_type in (0..3).map(LayerType::from_u8) {
+            if textures.tilesets[layer_type as usize].id() != *id {
+                continue;
+            }
+            info!("applying tileset of layer {}", layer_type.name());
+            textures.pack.insert(
+                layer_type,
+                TexturePack {
+                    texture: tileset.image.clone(),
+                    layout: tileset.layout.clone(),
+                    rules: tileset.rules.clone(),
+                    autotile: tileset.autotile.clone(),
+                },
+            );
+            for (mut texture, tilemap_layer_type) in &mut tilemaps {
+                if *tilemap_layer_type == layer_type {
+                    *texture = TilemapTexture::Single(tileset.image.clone());
+                }
+            }
+        }
+        if editor_meta.edit_mode {
+            commands.trigger(UiRespawnTrigger::TileSelectionRespawn);
+        }
+    }
+}
 fn load_level(
     mut events: EventReader<AssetEvent<SaveFile>>,
     mut commands: Commands,
     save_files: Res<Assets<SaveFile>>,
     mut tower_countdown: ResMut<TowerCountdown>,
+    mut editor_meta: ResMut<EditorMeta>,
+    mut map_size: ResMut<MapSize>,
+    time: Res<Time>,
     removable: Query<Entity, With<RemoveOnLevelSwap>>,
     textures: Res<map::Textures>,
-    mut maps: Query<(Entity, &mut TileStorage, &LayerType)>,
+    mut maps: Query<(Entity, &mut TileStorage, &mut TilemapSize, &LayerType)>,
 ) {
     for event in events.read() {
         match event {
             AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                // other levels might be loaded for previews
+                if *id != editor_meta.current_level.id() {
+                    continue;
+                }
                 for e in &removable {
                     commands.entity(e).despawn();
                 }
+                let level = save_files.get(*id).unwrap();
                 tower_countdown.timer = Some(Timer::from_seconds(0., TimerMode::Once));
                 tower_countdown.level_complete = false;
-                let level = save_files.get(*id).unwrap();
-                for (_e, mut storage, _) in &mut maps {
+                tower_countdown.duration = level
+                    .meta
+                    .tower_countdown
+                    .map(Duration::from_secs_f32)
+                    .unwrap_or(ACTIVE_TIME);
+                editor_meta.level_meta = level.meta.clone();
+                editor_meta.prefab_instances = level.prefabs.clone();
+                editor_meta.level_started = time.elapsed();
+                editor_meta.level_deads = 0;
+                commands.trigger(UiRespawnTrigger::LevelTitle);
+                map_size.0 = level.size.max(UVec2::ONE);
+                for (_e, mut storage, mut tilemap_size, _) in &mut maps {
                     storage
                         .drain()
                         .for_each(|tile| commands.entity(tile).despawn());
+                    *tilemap_size = map_size.0.into();
+                    *storage = TileStorage::empty(*tilemap_size);
                 }
                 for (layer_type, tiles) in &level.layers {
-                    let (tilemap_e, mut storage, _) = maps
+                    let (tilemap_e, mut storage, _, _) = maps
                         .iter_mut()
-                        .find(|(_e, _storage, map_layer_type)| layer_type == *map_layer_type)
+                        .find(|(_e, _storage, _, map_layer_type)| layer_type == *map_layer_type)
                         .unwrap();
                     let rules = &textures.pack[layer_type].rules;
                     for tile in &tiles.tiles {
+                        if tile.pos.cmpge(map_size.0).any() {
+                            warn!("tile at {} is outside of the map", tile.pos);
+                            continue;
+                        }
                         spawn_tile(
                             rules,
                             &mut commands,
@@ -374,6 +475,11 @@ pub fn spawn_tile(
     } else {
         commands.spawn((tile_pos, layer_type)).id()
     };
+    if !tile.properties.is_empty() {
+        commands
+            .entity(e)
+            .insert(TileProperties(tile.properties.clone()));
+    }
     if let Some(rule) = rule {
         commands.trigger_targets(*rule, e);
     }
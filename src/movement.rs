@@ -16,10 +16,10 @@ use bevy::{
 use crate::{
     MainCamera,
     animation::{AnimationConfig, EnemyAnimation, PlayerAnimation},
+    campaign::{Campaign, CampaignProgress},
     combat::{DashTargetedBy, DashTargeting, Tame},
-    editor::{EditorEvents, EditorMeta},
+    editor::{EditorEvents, EditorMeta, UiRespawnTrigger},
     entity::{Enemy, Pit, Player, PlayerController, PlayerMode, Portal},
-    io::SaveFile,
     map::{MousePosition, Textures},
     screens::GameState,
 };
@@ -332,8 +332,11 @@ fn check_collisions(
     player: Single<(&ChildOf, &PlayerAnimation), With<Player>>,
     pits: Query<(&CollidingEntities, &Pit), Changed<CollidingEntities>>,
     portals: Query<(&CollidingEntities, &Portal), (Without<Pit>, Changed<CollidingEntities>)>,
-    mut editor_meta: ResMut<EditorMeta>,
-    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    editor_meta: Res<EditorMeta>,
+    progress: Res<CampaignProgress>,
+    campaigns: Res<Assets<Campaign>>,
+    time: Res<Time>,
     mut event_writer: EventWriter<EditorEvents>,
 ) {
     let (player, animation) = *player;
@@ -349,13 +352,25 @@ fn check_collisions(
     }
     for (colliding_entities, portal) in &portals {
         if colliding_entities.contains(&player.0) && *portal == Portal::Open {
-            editor_meta.current_level_index += 1;
-            let number = editor_meta.current_level_index.to_string();
-
-            info!("start loading level {number}");
-            let handle =
-                asset_server.load::<SaveFile>(String::from("level/") + "level" + &number + ".ron");
-            editor_meta.current_level = handle;
+            let meta = &editor_meta.level_meta;
+            let time = (time.elapsed() - editor_meta.level_started).as_secs_f32();
+            info!(
+                "finished '{}' in {time:.1}s (par {:?}) with {} deaths (par {:?})",
+                meta.name, meta.par_time, editor_meta.level_deads, meta.par_deaths
+            );
+            // the level can override the order of the campaign
+            let next = meta.next.clone().or_else(|| {
+                let campaign = campaigns.get(progress.campaign.id())?;
+                let current = editor_meta.current_level_name.as_deref()?;
+                campaign.level_after(current).map(String::from)
+            });
+            if let Some(next) = next {
+                info!("start loading level {next}");
+                event_writer.write(EditorEvents::LoadLevel { name: Some(next) });
+            } else {
+                info!("campaign finished");
+                commands.trigger(UiRespawnTrigger::CampaignFinished);
+            }
         }
     }
 }
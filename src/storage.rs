@@ -0,0 +1,162 @@
+//! Where the editor writes levels and the level index to, see [`Storage`].
+//!
+//! Desktop builds write into the `assets` folder. The web build keeps written files in the
+//! `localStorage` of the browser and reads them in place of the bundled files,
+//! so saved levels load like any other level.
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::io::SaveError;
+
+/// Files written by the editor. Paths are asset paths like `level/level1.ron`
+pub trait Storage {
+    fn write(&self, path: &Path, content: &str) -> Result<(), SaveError>;
+    fn remove(&self, path: &Path) -> Result<(), SaveError>;
+    fn exists(&self, path: &Path) -> bool;
+}
+#[cfg(not(target_arch = "wasm32"))]
+pub fn plugin(_app: &mut App) {}
+/// Replaces the default asset source, so it has to be added before the `AssetPlugin`
+#[cfg(target_arch = "wasm32")]
+pub fn plugin(app: &mut App) {
+    app.register_asset_source(bevy::asset::io::AssetSourceId::Default, web::asset_source());
+}
+/// The storage of the current platform
+#[cfg(not(target_arch = "wasm32"))]
+pub fn storage() -> &'static dyn Storage {
+    &FileStorage
+}
+#[cfg(target_arch = "wasm32")]
+pub fn storage() -> &'static dyn Storage {
+    &web::LocalStorage
+}
+/// Writes into the `assets` folder
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage;
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    /// Writes to a temporary file first and moves it over `path` afterwards,
+    /// so a failed write never leaves a half written file behind
+    fn write(&self, path: &Path, content: &str) -> Result<(), SaveError> {
+        let path = Path::new("assets").join(path);
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        std::fs::write(&temp, content)?;
+        if let Err(err) = std::fs::rename(&temp, &path) {
+            let _ = std::fs::remove_file(&temp);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+    fn remove(&self, path: &Path) -> Result<(), SaveError> {
+        Ok(std::fs::remove_file(Path::new("assets").join(path))?)
+    }
+    fn exists(&self, path: &Path) -> bool {
+        Path::new("assets").join(path).exists()
+    }
+}
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::path::Path;
+
+    use bevy::asset::io::{
+        AssetReader, AssetReaderError, AssetSource, AssetSourceBuilder, ErasedAssetReader,
+        PathStream, Reader, VecReader,
+    };
+
+    use super::Storage;
+    use crate::io::{LEVEL_INDEX_PATH, LevelIndex, SaveError};
+
+    /// Keeps the keys apart from other pages of the same origin
+    const KEY_PREFIX: &str = "hnoss/";
+
+    fn key(path: &Path) -> String {
+        format!("{KEY_PREFIX}{}", path.to_string_lossy().replace('\\', "/"))
+    }
+    fn local_storage() -> Result<web_sys::Storage, SaveError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| SaveError::Browser("localStorage is not available".into()))
+    }
+    fn read_stored(path: &Path) -> Option<Vec<u8>> {
+        let content = local_storage().ok()?.get_item(&key(path)).ok()??;
+        Some(content.into_bytes())
+    }
+    /// Stores files in the `localStorage` of the browser.
+    /// Removing a bundled file only removes the stored version, the bundled one is served again afterwards
+    pub struct LocalStorage;
+    impl Storage for LocalStorage {
+        fn write(&self, path: &Path, content: &str) -> Result<(), SaveError> {
+            local_storage()?
+                .set_item(&key(path), content)
+                .map_err(|err| SaveError::Browser(format!("{err:?}")))
+        }
+        fn remove(&self, path: &Path) -> Result<(), SaveError> {
+            local_storage()?
+                .remove_item(&key(path))
+                .map_err(|err| SaveError::Browser(format!("{err:?}")))
+        }
+        fn exists(&self, path: &Path) -> bool {
+            read_stored(path).is_some()
+        }
+    }
+    /// Reads files from the `localStorage` and falls back to the bundled assets
+    struct LocalStorageReader {
+        fallback: Box<dyn ErasedAssetReader>,
+    }
+    impl LocalStorageReader {
+        /// Adds the levels of the bundled index to the stored one,
+        /// so levels shipped after the index was written still show up in the browser
+        async fn merge_index(&self, path: &Path, stored: Vec<u8>) -> Vec<u8> {
+            let Ok(mut reader) = self.fallback.read(path).await else {
+                return stored;
+            };
+            let mut bundled = Vec::new();
+            if reader.read_to_end(&mut bundled).await.is_err() {
+                return stored;
+            }
+            let (Ok(mut index), Ok(bundled)) = (
+                ron::de::from_bytes::<LevelIndex>(&stored),
+                ron::de::from_bytes::<LevelIndex>(&bundled),
+            ) else {
+                return stored;
+            };
+            index.merge(bundled);
+            ron::ser::to_string(&index).map_or(stored, String::into_bytes)
+        }
+    }
+    impl AssetReader for LocalStorageReader {
+        async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+            if let Some(mut bytes) = read_stored(path) {
+                if path == Path::new(LEVEL_INDEX_PATH) {
+                    bytes = self.merge_index(path, bytes).await;
+                }
+                return Ok(Box::new(VecReader::new(bytes)) as Box<dyn Reader + 'a>);
+            }
+            self.fallback.read(path).await
+        }
+        async fn read_meta<'a>(
+            &'a self,
+            path: &'a Path,
+        ) -> Result<impl Reader + 'a, AssetReaderError> {
+            self.fallback.read_meta(path).await
+        }
+        async fn read_directory<'a>(
+            &'a self,
+            path: &'a Path,
+        ) -> Result<Box<PathStream>, AssetReaderError> {
+            self.fallback.read_directory(path).await
+        }
+        async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+            self.fallback.is_directory(path).await
+        }
+    }
+    pub fn asset_source() -> AssetSourceBuilder {
+        AssetSourceBuilder::default().with_reader(|| {
+            Box::new(LocalStorageReader {
+                fallback: AssetSource::get_default_reader("assets".into())(),
+            })
+        })
+    }
+}
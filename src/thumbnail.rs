@@ -0,0 +1,150 @@
+//! Renders levels to images on the CPU, so previews can be made without a window or GPU.
+use std::{collections::HashMap, path::Path};
+
+use bevy::prelude::*;
+use image::{Rgba, RgbaImage, SubImage, imageops};
+use thiserror::Error;
+
+use crate::{
+    io::SaveFile,
+    map::{BACKGROUND_COLOR, LayerType, TILESIZE, TilesetDef},
+};
+
+#[derive(Debug, Error)]
+pub enum ThumbnailError {
+    #[error("Could not read file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+    #[error("Could not decode image: {0}")]
+    Image(#[from] image::ImageError),
+}
+/// The image of a tileset, cut into cells of [`TILESIZE`]
+pub struct TileAtlas {
+    image: RgbaImage,
+    columns: u32,
+    rows: u32,
+}
+impl TileAtlas {
+    /// `image` has to contain `columns` x `rows` cells
+    pub fn new(image: RgbaImage, columns: u32, rows: u32) -> TileAtlas {
+        TileAtlas {
+            image,
+            columns,
+            rows,
+        }
+    }
+    /// Reads the tileset of `layer_type` and its image from the `assets` directory
+    pub fn read(assets: &Path, layer_type: LayerType) -> Result<TileAtlas, ThumbnailError> {
+        let bytes = std::fs::read(assets.join(layer_type.tileset_path()))?;
+        let def = ron::de::from_bytes::<TilesetDef>(&bytes)?;
+        let image = image::open(assets.join(&def.image))?.into_rgba8();
+        Ok(TileAtlas {
+            image,
+            columns: def.columns,
+            rows: def.rows,
+        })
+    }
+    /// Reads the atlases of all layers
+    pub fn read_all(assets: &Path) -> Result<HashMap<LayerType, TileAtlas>, ThumbnailError> {
+        (0..3)
+            .map(LayerType::from_u8)
+            .map(|layer_type| Ok((layer_type, TileAtlas::read(assets, layer_type)?)))
+            .collect()
+    }
+    fn cell(&self, index: usize) -> Option<SubImage<&RgbaImage>> {
+        if index >= (self.columns * self.rows) as usize {
+            return None;
+        }
+        let size = TILESIZE as u32;
+        let (x, y) = (index as u32 % self.columns, index as u32 / self.columns);
+        Some(imageops::crop_imm(
+            &self.image,
+            x * size,
+            y * size,
+            size,
+            size,
+        ))
+    }
+}
+/// Draws the level with one pixel per texture pixel, layer by layer in the order of [`LayerType::z`].
+/// Tiles with an index outside of the atlas are skipped
+pub fn render_level(level: &SaveFile, atlases: &HashMap<LayerType, TileAtlas>) -> RgbaImage {
+    let size = TILESIZE as u32;
+    let color = BACKGROUND_COLOR.to_srgba();
+    let background =
+        Rgba([color.red, color.green, color.blue, color.alpha].map(|c| (c * 255.).round() as u8));
+    let mut image = RgbaImage::from_pixel(level.size.x * size, level.size.y * size, background);
+    for layer_type in (0..3).map(LayerType::from_u8) {
+        let (Some(layer), Some(atlas)) = (level.layers.get(&layer_type), atlases.get(&layer_type))
+        else {
+            continue;
+        };
+        for tile in &layer.tiles {
+            if tile.pos.cmpge(level.size).any() {
+                continue;
+            }
+            let Some(cell) = atlas.cell(tile.index) else {
+                continue;
+            };
+            // rows are counted from the bottom of the map but from the top of the image
+            let x = tile.pos.x * size;
+            let y = (level.size.y - 1 - tile.pos.y) * size;
+            imageops::overlay(&mut image, &cell.to_image(), x.into(), y.into());
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{Layer, Tile};
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    /// A red and a blue tile next to each other
+    fn atlas() -> TileAtlas {
+        let size = TILESIZE as u32;
+        let image = RgbaImage::from_fn(2 * size, size, |x, _| if x < size { RED } else { BLUE });
+        TileAtlas::new(image, 2, 1)
+    }
+    fn layer(tiles: &[(u32, u32, usize)]) -> Layer {
+        let tiles = tiles
+            .iter()
+            .map(|&(x, y, index)| Tile {
+                pos: UVec2::new(x, y),
+                index,
+                ..default()
+            })
+            .collect();
+        Layer { tiles }
+    }
+    #[test]
+    fn draws_layers_from_the_bottom_left() {
+        let level = SaveFile {
+            size: UVec2::new(2, 2),
+            layers: HashMap::from([
+                (LayerType::Bg, layer(&[(0, 0, 0), (1, 1, 0)])),
+                (LayerType::Fg, layer(&[(0, 0, 1)])),
+                // unknown indices and tiles outside of the map are skipped
+                (LayerType::Entities, layer(&[(1, 0, 2), (2, 0, 0)])),
+            ]),
+            ..default()
+        };
+        let atlases = HashMap::from([
+            (LayerType::Bg, atlas()),
+            (LayerType::Fg, atlas()),
+            (LayerType::Entities, atlas()),
+        ]);
+        let image = render_level(&level, &atlases);
+        let size = TILESIZE as u32;
+        assert_eq!(image.dimensions(), (2 * size, 2 * size));
+        assert_eq!(*image.get_pixel(0, size), BLUE);
+        assert_eq!(*image.get_pixel(size, 0), RED);
+        let background = Rgba([41, 41, 41, 255]);
+        assert_eq!(*image.get_pixel(0, 0), background);
+        assert_eq!(*image.get_pixel(size, size), background);
+    }
+}
@@ -0,0 +1,364 @@
+//! Import of maps made with [Tiled](https://www.mapeditor.org/) as `.tmx` or `.tmj` files.
+//!
+//! Tile layers named `Bg`, `Fg` and `Entities` become the layers of the [`SaveFile`], all other layers are ignored.
+//! Global tile ids are converted to the atlas index inside the tileset they belong to,
+//! so each layer should only use the tileset of its [`LayerType::tileset_path`].
+//! Flipped tiles are imported unflipped. Layer data has to use the CSV or XML format.
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use thiserror::Error;
+
+use crate::{
+    io::{Layer, SaveFile, Tile},
+    map::LayerType,
+};
+
+/// Tiled stores flipping and rotation in the highest bits of a global tile id
+const GID_FLAGS: u32 = 0xF000_0000;
+/// Largest width or height of an imported map, in tiles
+const MAX_MAP_SIZE: u32 = 1024;
+
+#[derive(Default)]
+pub struct TiledMapAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum TiledMapAssetLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not read map as text: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("Could not parse TMX: {0}")]
+    Xml(#[from] roxmltree::Error),
+    #[error("Could not parse TMJ: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported Tiled map: {0}")]
+    Unsupported(String),
+}
+impl AssetLoader for TiledMapAssetLoader {
+    type Asset = SaveFile;
+    type Settings = ();
+    type Error = TiledMapAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let path = load_context.path();
+        let map = if path.extension().is_some_and(|ext| ext == "tmj") {
+            TiledMap::from_tmj(&bytes)?
+        } else {
+            TiledMap::from_tmx(std::str::from_utf8(&bytes)?)?
+        };
+        let mut file = map.into_save_file()?;
+        if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+            file.meta.name = name.into();
+        }
+        Ok(file)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx", "tmj"]
+    }
+}
+/// The parts of a Tiled map that are shared by both file formats
+struct TiledMap {
+    width: u32,
+    height: u32,
+    /// `firstgid` of every tileset used by the map
+    first_gids: Vec<u32>,
+    /// Name and global tile ids of every tile layer, row by row from the top
+    layers: Vec<(String, Vec<u32>)>,
+}
+impl TiledMap {
+    fn from_tmx(text: &str) -> Result<TiledMap, TiledMapAssetLoaderError> {
+        fn attribute<T: std::str::FromStr>(
+            node: roxmltree::Node,
+            name: &str,
+        ) -> Result<T, TiledMapAssetLoaderError> {
+            node.attribute(name)
+                .and_then(|value| value.trim().parse().ok())
+                .ok_or_else(|| {
+                    TiledMapAssetLoaderError::Unsupported(format!(
+                        "<{}> is missing a valid '{name}'",
+                        node.tag_name().name()
+                    ))
+                })
+        }
+        let doc = roxmltree::Document::parse(text)?;
+        let map = doc.root_element();
+        if map.attribute("infinite") == Some("1") {
+            return Err(TiledMapAssetLoaderError::Unsupported(
+                "infinite maps can not be imported".into(),
+            ));
+        }
+        let mut tiled_map = TiledMap {
+            width: attribute(map, "width")?,
+            height: attribute(map, "height")?,
+            first_gids: Vec::new(),
+            layers: Vec::new(),
+        };
+        for node in map.children().filter(roxmltree::Node::is_element) {
+            match node.tag_name().name() {
+                "tileset" => tiled_map.first_gids.push(attribute(node, "firstgid")?),
+                "layer" => {
+                    let name = node.attribute("name").unwrap_or_default().to_string();
+                    let Some(data) = node.children().find(|child| child.has_tag_name("data"))
+                    else {
+                        continue;
+                    };
+                    let gids = match data.attribute("encoding") {
+                        None => data
+                            .children()
+                            .filter(|child| child.has_tag_name("tile"))
+                            .map(|tile| attribute(tile, "gid").unwrap_or(0))
+                            .collect(),
+                        Some("csv") => data
+                            .text()
+                            .unwrap_or_default()
+                            .split(',')
+                            .map(|gid| {
+                                gid.trim().parse().map_err(|_| {
+                                    TiledMapAssetLoaderError::Unsupported(format!(
+                                        "'{}' is not a tile id",
+                                        gid.trim()
+                                    ))
+                                })
+                            })
+                            .collect::<Result<_, _>>()?,
+                        Some(encoding) => {
+                            return Err(TiledMapAssetLoaderError::Unsupported(format!(
+                                "layer '{name}' uses the {encoding} encoding, save it as CSV"
+                            )));
+                        }
+                    };
+                    tiled_map.layers.push((name, gids));
+                }
+                _ => {}
+            }
+        }
+        Ok(tiled_map)
+    }
+    fn from_tmj(bytes: &[u8]) -> Result<TiledMap, TiledMapAssetLoaderError> {
+        #[derive(serde::Deserialize)]
+        struct TmjMap {
+            width: u32,
+            height: u32,
+            #[serde(default)]
+            infinite: bool,
+            layers: Vec<TmjLayer>,
+            #[serde(default)]
+            tilesets: Vec<TmjTileset>,
+        }
+        #[derive(serde::Deserialize)]
+        struct TmjLayer {
+            #[serde(default)]
+            name: String,
+            #[serde(rename = "type")]
+            kind: String,
+            data: Option<TmjData>,
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum TmjData {
+            Csv(Vec<u32>),
+            Encoded(String),
+        }
+        #[derive(serde::Deserialize)]
+        struct TmjTileset {
+            firstgid: u32,
+        }
+        let map = serde_json::from_slice::<TmjMap>(bytes)?;
+        if map.infinite {
+            return Err(TiledMapAssetLoaderError::Unsupported(
+                "infinite maps can not be imported".into(),
+            ));
+        }
+        let mut layers = Vec::new();
+        for layer in map.layers {
+            match layer.data {
+                _ if layer.kind != "tilelayer" => {}
+                Some(TmjData::Csv(gids)) => layers.push((layer.name, gids)),
+                Some(TmjData::Encoded(_)) => {
+                    return Err(TiledMapAssetLoaderError::Unsupported(format!(
+                        "layer '{}' is base64 encoded, save it as CSV",
+                        layer.name
+                    )));
+                }
+                None => {}
+            }
+        }
+        Ok(TiledMap {
+            width: map.width,
+            height: map.height,
+            first_gids: map
+                .tilesets
+                .iter()
+                .map(|tileset| tileset.firstgid)
+                .collect(),
+            layers,
+        })
+    }
+    fn into_save_file(self) -> Result<SaveFile, TiledMapAssetLoaderError> {
+        if self.width > MAX_MAP_SIZE || self.height > MAX_MAP_SIZE {
+            return Err(TiledMapAssetLoaderError::Unsupported(format!(
+                "map is {}x{} but at most {MAX_MAP_SIZE}x{MAX_MAP_SIZE} tiles are supported",
+                self.width, self.height
+            )));
+        }
+        let tile_count = (self.width as usize)
+            .checked_mul(self.height as usize)
+            .ok_or_else(|| {
+                TiledMapAssetLoaderError::Unsupported(format!(
+                    "map of {}x{} tiles is too large",
+                    self.width, self.height
+                ))
+            })?;
+        let mut file = SaveFile {
+            size: UVec2::new(self.width, self.height),
+            ..default()
+        };
+        for (name, gids) in self.layers {
+            let layer_type = match name.as_str() {
+                "Bg" => LayerType::Bg,
+                "Fg" => LayerType::Fg,
+                "Entities" => LayerType::Entities,
+                _ => {
+                    info!("ignoring Tiled layer '{name}'");
+                    continue;
+                }
+            };
+            if gids.len() != tile_count {
+                return Err(TiledMapAssetLoaderError::Unsupported(format!(
+                    "layer '{name}' has {} tiles but the map is {}x{}",
+                    gids.len(),
+                    self.width,
+                    self.height
+                )));
+            }
+            let tiles = gids
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, gid)| {
+                    let gid = gid & !GID_FLAGS;
+                    // the tileset with the highest `firstgid` not above the id contains the tile
+                    let first_gid = self
+                        .first_gids
+                        .iter()
+                        .filter(|first_gid| **first_gid <= gid)
+                        .max()?;
+                    let (column, row) = (i as u32 % self.width, i as u32 / self.width);
+                    Some(Tile {
+                        // Tiled counts rows from the top
+                        pos: UVec2::new(column, self.height - 1 - row),
+                        index: (gid - first_gid) as usize,
+                        ..default()
+                    })
+                })
+                .collect();
+            file.layers.insert(layer_type, Layer { tiles });
+        }
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_tmx_with_several_tilesets() {
+        let map = TiledMap::from_tmx(
+            r#"<map width="3" height="2">
+                <tileset firstgid="1" source="bg.tsx"/>
+                <tileset firstgid="65" source="fg.tsx"/>
+                <layer name="Fg" width="3" height="2">
+                    <data encoding="csv">0,65,0,
+                    2147483714,0,3</data>
+                </layer>
+                <layer name="Decoration"><data encoding="csv">1,1,1,1,1,1</data></layer>
+            </map>"#,
+        )
+        .unwrap();
+        let file = map.into_save_file().unwrap();
+        assert_eq!(file.size, UVec2::new(3, 2));
+        assert_eq!(file.layers.len(), 1);
+        let tiles: Vec<_> = file.layers[&LayerType::Fg]
+            .tiles
+            .iter()
+            .map(|tile| (tile.pos, tile.index))
+            .collect();
+        // the flipped tile is imported unflipped and tile 3 belongs to the first tileset
+        assert_eq!(
+            tiles,
+            [
+                (UVec2::new(1, 1), 0),
+                (UVec2::new(0, 0), 1),
+                (UVec2::new(2, 0), 2)
+            ]
+        );
+    }
+    #[test]
+    fn reads_tmx_tile_elements() {
+        let map = TiledMap::from_tmx(
+            r#"<map width="2" height="1">
+                <tileset firstgid="1" source="entities.tsx"/>
+                <layer name="Entities"><data><tile/><tile gid="4"/></data></layer>
+            </map>"#,
+        )
+        .unwrap();
+        let file = map.into_save_file().unwrap();
+        let tile = &file.layers[&LayerType::Entities].tiles[0];
+        assert_eq!((tile.pos, tile.index), (UVec2::new(1, 0), 3));
+    }
+    #[test]
+    fn reads_tmj() {
+        let map = TiledMap::from_tmj(
+            br#"{"width": 2, "height": 2, "tilesets": [{"firstgid": 1}],
+                "layers": [
+                    {"name": "Bg", "type": "tilelayer", "data": [5, 0, 0, 1073741830]},
+                    {"name": "Objects", "type": "objectgroup"}
+                ]}"#,
+        )
+        .unwrap();
+        let file = map.into_save_file().unwrap();
+        let tiles: Vec<_> = file.layers[&LayerType::Bg]
+            .tiles
+            .iter()
+            .map(|tile| (tile.pos, tile.index))
+            .collect();
+        assert_eq!(tiles, [(UVec2::new(0, 1), 4), (UVec2::new(1, 0), 5)]);
+    }
+    #[test]
+    fn rejects_encoded_and_incomplete_layers() {
+        let encoded = TiledMap::from_tmx(
+            r#"<map width="1" height="1">
+                <layer name="Fg"><data encoding="base64">AQAAAA==</data></layer>
+            </map>"#,
+        );
+        assert!(matches!(
+            encoded,
+            Err(TiledMapAssetLoaderError::Unsupported(_))
+        ));
+        let incomplete = TiledMap::from_tmj(
+            br#"{"width": 2, "height": 2, "layers": [{"name": "Fg", "type": "tilelayer", "data": [1]}]}"#,
+        )
+        .unwrap();
+        assert!(incomplete.into_save_file().is_err());
+    }
+    #[test]
+    fn rejects_huge_maps() {
+        let map = TiledMap::from_tmj(
+            br#"{"width": 65536, "height": 65536, "layers": [{"name": "Fg", "type": "tilelayer", "data": []}]}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            map.into_save_file(),
+            Err(TiledMapAssetLoaderError::Unsupported(_))
+        ));
+    }
+}
@@ -1,10 +1,10 @@
-use bevy::math::{IVec2, Vec2, Vec3};
+use bevy::math::{IVec2, UVec2, Vec2, Vec3};
 use bevy_ecs_tilemap::{
     map::{TilemapTileSize, TilemapType},
     tiles::TilePos,
 };
 
-use crate::map::{TILEMAP_ANCHOR, TILEMAP_MAPSIZE, TILESIZE};
+use crate::map::{TILEMAP_ANCHOR, TILESIZE};
 
 pub fn iter_grid_rect(start: TilePos, end: TilePos) -> Vec<TilePos> {
     let start = IVec2::new(start.x as i32, start.y as i32);
@@ -21,13 +21,39 @@ pub fn iter_grid_rect(start: TilePos, end: TilePos) -> Vec<TilePos> {
     }
     v
 }
-pub fn tile_to_world(tile: &TilePos, tilemap_translation: Vec3) -> Vec3 {
+/// Tiles on the straight line from `start` to `end`, including both ends
+pub fn iter_grid_line(start: TilePos, end: TilePos) -> Vec<TilePos> {
+    let mut current = IVec2::new(start.x as i32, start.y as i32);
+    let end = IVec2::new(end.x as i32, end.y as i32);
+    let delta = (end - current).abs();
+    let step = (end - current).signum();
+    let mut error = delta.x - delta.y;
+    let mut v = Vec::with_capacity(delta.max_element() as usize + 1);
+    loop {
+        v.push(TilePos::new(current.x as u32, current.y as u32));
+        if current == end {
+            return v;
+        }
+        // bresenham
+        let e2 = 2 * error;
+        if e2 > -delta.y {
+            error -= delta.y;
+            current.x += step.x;
+        }
+        if e2 < delta.x {
+            error += delta.x;
+            current.y += step.y;
+        }
+    }
+}
+/// `map_size` is the size of the active map, see [`crate::map::MapSize`]
+pub fn tile_to_world(tile: &TilePos, map_size: UVec2, tilemap_translation: Vec3) -> Vec3 {
     let tile_size = TilemapTileSize {
         x: TILESIZE as f32,
         y: TILESIZE as f32,
     };
     tile.center_in_world(
-        &TILEMAP_MAPSIZE.into(),
+        &map_size.into(),
         &tile_size.into(),
         &tile_size,
         &TilemapType::Square,
@@ -36,14 +62,18 @@ pub fn tile_to_world(tile: &TilePos, tilemap_translation: Vec3) -> Vec3 {
     .extend(0.)
         + tilemap_translation
 }
-pub fn world_to_tilepos(position: Vec2, tilemap_translation: Vec2) -> Option<TilePos> {
+pub fn world_to_tilepos(
+    position: Vec2,
+    map_size: UVec2,
+    tilemap_translation: Vec2,
+) -> Option<TilePos> {
     let tile_size = TilemapTileSize {
         x: TILESIZE as f32,
         y: TILESIZE as f32,
     };
     TilePos::from_world_pos(
         &(position - tilemap_translation),
-        &TILEMAP_MAPSIZE.into(),
+        &map_size.into(),
         &tile_size.into(),
         &tile_size,
         &TilemapType::Square,
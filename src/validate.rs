@@ -0,0 +1,343 @@
+//! Checks levels for problems that make them unplayable or load differently than they look in the editor.
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::{
+    combat::ENEMY_EXPLOSION_RADIUS,
+    entity::{OnSpawnTrigger, Rule},
+    io::SaveFile,
+    map::{LayerType, TILESIZE, TilesetDef},
+};
+
+/// What the validator needs to know about the tileset of a layer
+pub struct TilesetInfo {
+    /// Number of tiles in the atlas. Higher indices are unknown
+    pub tile_count: usize,
+    pub rules: Vec<Rule>,
+}
+impl From<TilesetDef> for TilesetInfo {
+    fn from(def: TilesetDef) -> Self {
+        TilesetInfo {
+            tile_count: (def.columns * def.rows) as usize,
+            rules: def.rules,
+        }
+    }
+}
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+    NoPlayer,
+    MultiplePlayers,
+    NoPortal,
+    /// The portal only opens once all towers are lit, so it never opens without towers
+    NoTowers,
+    OutOfBounds,
+    UnknownIndex,
+    DuplicatePosition,
+    /// No enemy spawns within [`ENEMY_EXPLOSION_RADIUS`], so the tower can not be lit
+    UnlitTower,
+    EnemyOnCollider,
+    /// Walls separate the portal from the player spawn
+    UnreachablePortal,
+}
+impl ProblemKind {
+    pub fn severity(self) -> Severity {
+        match self {
+            ProblemKind::DuplicatePosition
+            | ProblemKind::UnlitTower
+            | ProblemKind::EnemyOnCollider
+            | ProblemKind::UnreachablePortal => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+/// Errors make a level unplayable, warnings point at something that is likely a mistake
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+#[derive(serde::Serialize, Debug, Clone)]
+pub struct Problem {
+    pub kind: ProblemKind,
+    pub severity: Severity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer: Option<LayerType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pos: Option<UVec2>,
+}
+impl Problem {
+    fn level(kind: ProblemKind, message: impl Into<String>) -> Problem {
+        Problem {
+            kind,
+            severity: kind.severity(),
+            message: message.into(),
+            layer: None,
+            pos: None,
+        }
+    }
+    fn tile(
+        kind: ProblemKind,
+        layer: LayerType,
+        pos: UVec2,
+        message: impl Into<String>,
+    ) -> Problem {
+        Problem {
+            kind,
+            severity: kind.severity(),
+            message: message.into(),
+            layer: Some(layer),
+            pos: Some(pos),
+        }
+    }
+}
+/// A tile with a rule
+struct Spawn {
+    trigger: OnSpawnTrigger,
+    layer: LayerType,
+    pos: UVec2,
+}
+pub fn validate_level(
+    level: &SaveFile,
+    tilesets: &HashMap<LayerType, TilesetInfo>,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut spawns = Vec::new();
+    // iterate in a fixed order so the output is stable
+    for layer_type in (0..3).map(LayerType::from_u8) {
+        let Some(layer) = level.layers.get(&layer_type) else {
+            continue;
+        };
+        let tileset = tilesets.get(&layer_type);
+        let mut positions = HashSet::new();
+        for tile in &layer.tiles {
+            let name = layer_type.name();
+            if tile.pos.cmpge(level.size).any() {
+                problems.push(Problem::tile(
+                    ProblemKind::OutOfBounds,
+                    layer_type,
+                    tile.pos,
+                    format!(
+                        "tile at {} in {name} is outside of the {}x{} map",
+                        tile.pos, level.size.x, level.size.y
+                    ),
+                ));
+            }
+            if !positions.insert(tile.pos) {
+                problems.push(Problem::tile(
+                    ProblemKind::DuplicatePosition,
+                    layer_type,
+                    tile.pos,
+                    format!("{name} has more than one tile at {}", tile.pos),
+                ));
+            }
+            let Some(tileset) = tileset else {
+                continue;
+            };
+            if tile.index >= tileset.tile_count {
+                problems.push(Problem::tile(
+                    ProblemKind::UnknownIndex,
+                    layer_type,
+                    tile.pos,
+                    format!(
+                        "tile at {} in {name} has index {} but the tileset only has {} tiles",
+                        tile.pos, tile.index, tileset.tile_count
+                    ),
+                ));
+            }
+            if let Some(rule) = tileset.rules.iter().find(|r| r.target_index == tile.index) {
+                spawns.push(Spawn {
+                    trigger: rule.on_spawn,
+                    layer: layer_type,
+                    pos: tile.pos,
+                });
+            }
+        }
+    }
+    let find = |wanted: &'static [OnSpawnTrigger]| {
+        spawns
+            .iter()
+            .filter(move |spawn| wanted.contains(&spawn.trigger))
+    };
+    let count = |wanted: &'static [OnSpawnTrigger]| find(wanted).count();
+    match count(&[OnSpawnTrigger::Player, OnSpawnTrigger::PlayerSpawnPlatform]) {
+        0 => problems.push(Problem::level(
+            ProblemKind::NoPlayer,
+            "level has no player or player spawn platform",
+        )),
+        1 => {}
+        n => problems.push(Problem::level(
+            ProblemKind::MultiplePlayers,
+            format!("level has {n} players but only the last one is spawned"),
+        )),
+    }
+    let portals = count(&[OnSpawnTrigger::Portal]);
+    if portals + count(&[OnSpawnTrigger::GameFinishedPlatform]) == 0 {
+        problems.push(Problem::level(
+            ProblemKind::NoPortal,
+            "level has no portal and can not be finished",
+        ));
+    }
+    if portals > 0 && count(&[OnSpawnTrigger::Tower]) == 0 {
+        problems.push(Problem::level(
+            ProblemKind::NoTowers,
+            "level has no towers so the portal never opens",
+        ));
+    }
+    let colliders: HashSet<UVec2> = find(&[OnSpawnTrigger::Collider])
+        .map(|spawn| spawn.pos)
+        .collect();
+    for tower in find(&[OnSpawnTrigger::Tower]) {
+        let lit = find(&[OnSpawnTrigger::Enemy]).any(|enemy| {
+            enemy.pos.as_vec2().distance(tower.pos.as_vec2()) * (TILESIZE as f32)
+                < ENEMY_EXPLOSION_RADIUS
+        });
+        if !lit {
+            problems.push(Problem::tile(
+                ProblemKind::UnlitTower,
+                tower.layer,
+                tower.pos,
+                format!("no enemy can light the tower at {}", tower.pos),
+            ));
+        }
+    }
+    for enemy in find(&[OnSpawnTrigger::Enemy]) {
+        if colliders.contains(&enemy.pos) {
+            problems.push(Problem::tile(
+                ProblemKind::EnemyOnCollider,
+                enemy.layer,
+                enemy.pos,
+                format!("enemy at {} spawns inside a wall", enemy.pos),
+            ));
+        }
+    }
+    let player = find(&[OnSpawnTrigger::Player, OnSpawnTrigger::PlayerSpawnPlatform]).last();
+    if let Some(player) = player {
+        let reachable = reachable_tiles(player.pos, level.size, &colliders);
+        for portal in find(&[OnSpawnTrigger::Portal, OnSpawnTrigger::GameFinishedPlatform]) {
+            if !reachable.contains(&portal.pos) {
+                problems.push(Problem::tile(
+                    ProblemKind::UnreachablePortal,
+                    portal.layer,
+                    portal.pos,
+                    format!(
+                        "portal at {} can not be reached from the player at {}",
+                        portal.pos, player.pos
+                    ),
+                ));
+            }
+        }
+    }
+    problems
+}
+/// Tiles that can be walked to from `start` without crossing `blocked` tiles
+fn reachable_tiles(start: UVec2, size: UVec2, blocked: &HashSet<UVec2>) -> HashSet<UVec2> {
+    let mut reachable = HashSet::from([start]);
+    let mut open = vec![start];
+    while let Some(pos) = open.pop() {
+        let neighbours = [
+            Some(pos + UVec2::X),
+            Some(pos + UVec2::Y),
+            pos.x.checked_sub(1).map(|x| UVec2::new(x, pos.y)),
+            pos.y.checked_sub(1).map(|y| UVec2::new(pos.x, y)),
+        ];
+        for neighbour in neighbours.into_iter().flatten() {
+            if neighbour.cmplt(size).all()
+                && !blocked.contains(&neighbour)
+                && reachable.insert(neighbour)
+            {
+                open.push(neighbour);
+            }
+        }
+    }
+    reachable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tilesets() -> HashMap<LayerType, TilesetInfo> {
+        let info = |rules: &str| TilesetInfo {
+            tile_count: 8,
+            rules: ron::from_str(rules).unwrap(),
+        };
+        HashMap::from([
+            (LayerType::Bg, info("[]")),
+            (LayerType::Fg, info("[(index: 0, trigger: Collider)]")),
+            (
+                LayerType::Entities,
+                info(
+                    "[(index: 0, trigger: Player), (index: 1, trigger: Portal),
+                      (index: 2, trigger: Tower), (index: 3, trigger: Enemy)]",
+                ),
+            ),
+        ])
+    }
+    /// A 6x3 level with the player on the left and the portal, tower and enemy on the right
+    fn level(fg: &str) -> SaveFile {
+        ron::from_str(&format!(
+            "(version: 5, size: (6, 3), layers: {{
+                Fg: (tiles: [{fg}]),
+                Entities: (tiles: [(pos: (0, 0), index: 0), (pos: (5, 0), index: 1),
+                                   (pos: (4, 0), index: 2), (pos: (4, 1), index: 3)]),
+            }})"
+        ))
+        .unwrap()
+    }
+    fn kinds(problems: &[Problem]) -> Vec<ProblemKind> {
+        problems.iter().map(|problem| problem.kind).collect()
+    }
+    #[test]
+    fn accepts_playable_level() {
+        let problems = validate_level(&level("(pos: (2, 0), index: 0)"), &tilesets());
+        assert!(problems.is_empty(), "{problems:?}");
+    }
+    #[test]
+    fn warns_about_walled_off_portal() {
+        let wall = "(pos: (2, 0), index: 0), (pos: (2, 1), index: 0), (pos: (2, 2), index: 0)";
+        let problems = validate_level(&level(wall), &tilesets());
+        assert_eq!(kinds(&problems), [ProblemKind::UnreachablePortal]);
+        assert_eq!(problems[0].severity, Severity::Warning);
+        assert_eq!(problems[0].pos, Some(UVec2::new(5, 0)));
+    }
+    #[test]
+    fn reports_tiles_that_do_not_fit() {
+        let fg = "(pos: (6, 0), index: 1), (pos: (1, 1), index: 1), (pos: (1, 1), index: 8)";
+        let problems = validate_level(&level(fg), &tilesets());
+        assert_eq!(
+            kinds(&problems),
+            [
+                ProblemKind::OutOfBounds,
+                ProblemKind::DuplicatePosition,
+                ProblemKind::UnknownIndex
+            ]
+        );
+    }
+    #[test]
+    fn rejects_empty_level() {
+        let problems = validate_level(&SaveFile::default(), &tilesets());
+        assert_eq!(
+            kinds(&problems),
+            [ProblemKind::NoPlayer, ProblemKind::NoPortal]
+        );
+        assert!(
+            problems
+                .iter()
+                .all(|problem| problem.severity == Severity::Error)
+        );
+    }
+    #[test]
+    fn reaches_tiles_around_walls() {
+        let blocked = HashSet::from([UVec2::new(1, 0), UVec2::new(1, 1)]);
+        let reachable = reachable_tiles(UVec2::ZERO, UVec2::new(3, 3), &blocked);
+        assert_eq!(reachable.len(), 7);
+        assert!(reachable.contains(&UVec2::new(2, 0)));
+        assert!(!reachable.contains(&UVec2::new(1, 0)));
+        let reachable = reachable_tiles(UVec2::ZERO, UVec2::new(3, 2), &blocked);
+        assert_eq!(reachable, HashSet::from([UVec2::ZERO, UVec2::new(0, 1)]));
+    }
+}